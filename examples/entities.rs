@@ -0,0 +1,139 @@
+//! An example using entities, managed by an entity registry.
+//!
+//! This provides the same device as the `raw` example: a switch, which enables a motion sensor
+//! toggling every 5 seconds.
+
+use clap::Parser;
+use homeassistant_agent::{
+    connector::{ClientError, Connector, ConnectorOptions},
+    entity::{Entity, EntityHandle, EntityRegistry},
+    model::{
+        BinarySensorClass, BinarySensorDiscovery, Component, Device, DeviceId, Discovery, Origin,
+        SwitchCommand, SwitchDiscovery,
+    },
+};
+use std::{convert::Infallible, time::Duration};
+use tokio::sync::watch;
+
+#[derive(Debug, clap::Parser)]
+struct Cli {
+    #[command(flatten)]
+    connector: ConnectorOptions,
+}
+
+fn device() -> Device {
+    Device::new("test-id2")
+        .name("Test Device 2")
+        .manufacturer("ACME")
+        .model("Example")
+}
+
+struct Motion;
+
+impl Entity for Motion {
+    type Command = Infallible;
+    type Error = Infallible;
+
+    fn id(&self) -> DeviceId {
+        DeviceId::new("test-id20-motion", Component::BinarySensor)
+    }
+
+    fn discovery(&self) -> Discovery {
+        Discovery {
+            device: Some(device()),
+            ..BinarySensorDiscovery::new("~/state")
+                .device_class(BinarySensorClass::Motion)
+                .build()
+        }
+    }
+
+    async fn command(&mut self, _: &EntityHandle, command: Infallible) -> Result<(), Infallible> {
+        match command {}
+    }
+}
+
+struct Switch {
+    enabled: watch::Sender<bool>,
+}
+
+impl Entity for Switch {
+    type Command = SwitchCommand;
+    type Error = ClientError;
+
+    fn id(&self) -> DeviceId {
+        DeviceId::new("test-id20-switch", Component::Switch)
+    }
+
+    fn discovery(&self) -> Discovery {
+        Discovery {
+            device: Some(device()),
+            ..SwitchDiscovery::new("~/command")
+                .state_topic("~/state")
+                .build()
+        }
+    }
+
+    async fn command(
+        &mut self,
+        handle: &EntityHandle,
+        command: SwitchCommand,
+    ) -> Result<(), ClientError> {
+        log::info!("Switch command: {command:?}");
+
+        let state = command == SwitchCommand::TurnOn;
+        self.enabled.send_replace(state);
+        handle.publish_state(state).await
+    }
+}
+
+/// Toggle the motion sensor, while the switch is enabled.
+async fn run_motion(motion: EntityHandle, mut enabled: watch::Receiver<bool>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    let mut state = false;
+
+    loop {
+        if *enabled.borrow_and_update() {
+            interval.tick().await;
+            state = !state;
+        } else if enabled.changed().await.is_err() {
+            break;
+        } else {
+            continue;
+        }
+
+        if let Err(err) = motion.publish_state(state).await {
+            log::warn!("Failed to update motion state: {err}");
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    log::info!("Starting up example");
+
+    let connector = Connector::new(cli.connector, |client| {
+        let mut registry = EntityRegistry::new(client, "my-base");
+
+        let (tx, rx) = watch::channel(false);
+        let motion = registry.add(Motion);
+        registry.add(Switch { enabled: tx });
+
+        tokio::spawn(run_motion(motion, rx));
+
+        registry
+    })
+    .origin(Origin::new("entities-example").sw_version(env!("CARGO_PKG_VERSION")));
+    connector
+        .run_until(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    log::info!("Exiting");
+
+    Ok(())
+}
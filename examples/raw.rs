@@ -7,7 +7,7 @@ use bytes::Bytes;
 use clap::Parser;
 use homeassistant_agent::{
     connector::{Client, ClientError, Connector, ConnectorHandler, ConnectorOptions},
-    model::{BinarySensorClass, Component, Device, DeviceId, Discovery},
+    model::{BinarySensorClass, Component, Device, DeviceId, Discovery, Origin},
 };
 use rumqttc::QoS;
 use std::time::Duration;
@@ -56,7 +56,7 @@ impl MotionDevice {
         let discovery = Discovery {
             unique_id: Some(id.id.to_string()),
             device: Some(device),
-            device_class: device_class.map(|c| c.as_ref().to_string()),
+            device_class: device_class.map(Into::into),
             state_topic: Some(state_topic.clone()),
             ..Default::default()
         };
@@ -99,13 +99,10 @@ impl SwitchDevice {
 
 impl CustomDevice {
     pub fn new(client: Client) -> Self {
-        let device = Device {
-            name: Some("Test Device 1".to_string()),
-            base_topic: None,
-            identifiers: vec!["test-id1".to_string()],
-            support_url: None,
-            sw_version: None,
-        };
+        let device = Device::new("test-id1")
+            .name("Test Device 1")
+            .manufacturer("ACME")
+            .model("Example");
 
         let motion = MotionDevice::new(
             "my-base",
@@ -265,7 +262,8 @@ async fn main() -> anyhow::Result<()> {
 
     log::info!("Starting up example");
 
-    let connector = Connector::new(cli.connector, CustomDevice::new);
+    let connector = Connector::new(cli.connector, CustomDevice::new)
+        .origin(Origin::new("raw-example").sw_version(env!("CARGO_PKG_VERSION")));
     connector.run().await?;
 
     log::info!("Exiting");
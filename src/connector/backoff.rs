@@ -0,0 +1,109 @@
+use crate::connector::ConnectorOptions;
+use rand::Rng;
+use std::time::Duration;
+
+/// Exponential backoff for reconnecting, with random jitter.
+#[derive(Clone, Debug)]
+pub(crate) struct Backoff {
+    min: Duration,
+    max: Duration,
+    jitter: Duration,
+    max_attempts: Option<u32>,
+    attempts: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new(options: &ConnectorOptions) -> Self {
+        Self {
+            min: options.reconnect_min_delay,
+            max: options.reconnect_max_delay.max(options.reconnect_min_delay),
+            jitter: options.reconnect_jitter,
+            max_attempts: options.reconnect_max_attempts,
+            attempts: 0,
+        }
+    }
+
+    /// The number of failed attempts, since the last reset.
+    pub(crate) fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Record a failed attempt, returns the delay before the next one, or `None` if the maximum
+    /// number of attempts is reached.
+    pub(crate) fn next(&mut self) -> Option<Duration> {
+        self.attempts = self.attempts.saturating_add(1);
+        if let Some(max_attempts) = self.max_attempts {
+            if self.attempts >= max_attempts {
+                return None;
+            }
+        }
+
+        Some(self.delay() + self.random_jitter())
+    }
+
+    /// Reset after a successful connection.
+    pub(crate) fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    fn delay(&self) -> Duration {
+        let factor = 2u32.saturating_pow(self.attempts.saturating_sub(1));
+        self.min.saturating_mul(factor).min(self.max)
+    }
+
+    fn random_jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn backoff(max_attempts: Option<u32>) -> Backoff {
+        Backoff {
+            min: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            jitter: Duration::ZERO,
+            max_attempts,
+            attempts: 0,
+        }
+    }
+
+    #[test]
+    fn test_exponential() {
+        let mut backoff = backoff(None);
+        let delays = (0..6)
+            .map(|_| backoff.next().map(|d| d.as_secs()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            vec![Some(1), Some(2), Some(4), Some(8), Some(10), Some(10)]
+        );
+
+        backoff.reset();
+        assert_eq!(backoff.next(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let mut backoff = backoff(Some(3));
+        assert!(backoff.next().is_some());
+        assert!(backoff.next().is_some());
+        assert!(backoff.next().is_none());
+        assert_eq!(backoff.attempts(), 3);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut backoff = Backoff {
+            jitter: Duration::from_millis(500),
+            ..backoff(None)
+        };
+        let delay = backoff.next().unwrap();
+        assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_millis(1500));
+    }
+}
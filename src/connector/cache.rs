@@ -0,0 +1,144 @@
+use crate::connector::PublishOptions;
+use std::collections::BTreeMap;
+
+/// A message, as it was last published.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedMessage {
+    pub(crate) payload: Vec<u8>,
+    pub(crate) options: PublishOptions,
+    /// If the message still needs to be published on the current connection.
+    pub(crate) pending: bool,
+}
+
+/// The last announcements and states, by their topic, which get replayed after reconnecting or
+/// Home Assistant restarting.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Cache {
+    announcements: BTreeMap<String, CachedMessage>,
+    states: BTreeMap<String, CachedMessage>,
+}
+
+impl Cache {
+    pub(crate) fn announced(&mut self, topic: &str, payload: &[u8], options: &PublishOptions) {
+        Self::insert(&mut self.announcements, topic, payload, options, false);
+    }
+
+    /// Add an announcement, which gets published with the next replay.
+    pub(crate) fn registered(&mut self, topic: &str, payload: &[u8], options: &PublishOptions) {
+        Self::insert(&mut self.announcements, topic, payload, options, true);
+    }
+
+    /// Forget an announcement, which was removed.
+    pub(crate) fn unannounced(&mut self, topic: &str) {
+        self.announcements.remove(topic);
+    }
+
+    pub(crate) fn state(&mut self, topic: &str, payload: &[u8], options: &PublishOptions) {
+        Self::insert(&mut self.states, topic, payload, options, false);
+    }
+
+    /// Mark all messages as pending, as they need to be published again.
+    pub(crate) fn invalidate(&mut self) {
+        for message in self
+            .announcements
+            .values_mut()
+            .chain(self.states.values_mut())
+        {
+            message.pending = true;
+        }
+    }
+
+    /// Mark a message as published, after it was replayed.
+    pub(crate) fn replayed(&mut self, topic: &str) {
+        for messages in [&mut self.announcements, &mut self.states] {
+            if let Some(message) = messages.get_mut(topic) {
+                message.pending = false;
+            }
+        }
+    }
+
+    fn insert(
+        messages: &mut BTreeMap<String, CachedMessage>,
+        topic: &str,
+        payload: &[u8],
+        options: &PublishOptions,
+        pending: bool,
+    ) {
+        messages.insert(
+            topic.to_string(),
+            CachedMessage {
+                payload: payload.to_vec(),
+                options: options.clone(),
+                pending,
+            },
+        );
+    }
+
+    /// All messages to replay, announcements first.
+    ///
+    /// Messages which were published since the cache was invalidated are skipped.
+    pub(crate) fn pending(&self) -> Vec<(String, CachedMessage)> {
+        self.announcements
+            .iter()
+            .chain(self.states.iter())
+            .filter(|(_, message)| message.pending)
+            .map(|(topic, message)| (topic.clone(), message.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_replay_order() {
+        let options = PublishOptions::default();
+        let mut cache = Cache::default();
+
+        cache.state("a/state", b"ON", &options);
+        cache.announced("a/config", b"{}", &options);
+        cache.announced("b/config", b"{}", &options);
+        cache.state("a/state", b"OFF", &options);
+        cache.unannounced("b/config");
+        cache.invalidate();
+
+        let messages = cache
+            .pending()
+            .into_iter()
+            .map(|(topic, message)| (topic, message.payload))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                ("a/config".to_string(), b"{}".to_vec()),
+                ("a/state".to_string(), b"OFF".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending() {
+        let options = PublishOptions::default();
+        let mut cache = Cache::default();
+
+        cache.registered("a/config", b"{}", &options);
+        cache.state("a/state", b"ON", &options);
+        assert_eq!(pending_topics(&cache), vec!["a/config"]);
+
+        // published again by the handler, or by a replay
+        cache.invalidate();
+        cache.state("a/state", b"OFF", &options);
+        assert_eq!(pending_topics(&cache), vec!["a/config"]);
+        cache.replayed("a/config");
+        assert!(pending_topics(&cache).is_empty());
+    }
+
+    fn pending_topics(cache: &Cache) -> Vec<String> {
+        cache
+            .pending()
+            .into_iter()
+            .map(|(topic, _)| topic)
+            .collect()
+    }
+}
@@ -1,32 +1,201 @@
-use crate::model::{DeviceId, Discovery};
-use rumqttc::{AsyncClient, QoS};
+use crate::{
+    connector::{Cache, Message, MessageProperties, MqttClient, MqttClientError},
+    model::{
+        DeviceDiscovery, DeviceDiscoveryId, DeviceId, Discovery, Event, KeyFormat, Origin,
+        StateError, StateValue,
+    },
+};
+use rumqttc::QoS;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The time to wait for the retained discovery messages of a cleanup.
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, thiserror::Error)]
 pub enum ClientError {
     #[error("serialization failure")]
     Serialization(#[from] serde_json::Error),
     #[error("client error")]
-    Client(#[from] rumqttc::ClientError),
+    Client(#[from] MqttClientError),
+    #[error("missing topic: {0}")]
+    MissingTopic(&'static str),
+    #[error("invalid state")]
+    State(#[from] StateError),
+    #[error("unknown event type: {0:?}")]
+    UnknownEventType(String),
+    /// The node ID is empty, or contains a wildcard or topic separator.
+    #[error("invalid node ID: {0:?}")]
+    InvalidNodeId(String),
+}
+
+/// Options for publishing a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublishOptions {
+    pub qos: QoS,
+    pub retain: bool,
+    /// The time after which the broker drops the message, if it wasn't delivered yet.
+    ///
+    /// This requires MQTT v5, and is ignored otherwise.
+    pub message_expiry: Option<Duration>,
+    /// Additional properties of the message.
+    ///
+    /// This requires MQTT v5, and is ignored otherwise.
+    pub properties: MessageProperties,
+}
+
+impl Default for PublishOptions {
+    fn default() -> Self {
+        Self {
+            qos: QoS::AtLeastOnce,
+            retain: false,
+            message_expiry: None,
+            properties: Default::default(),
+        }
+    }
+}
+
+impl PublishOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    pub fn message_expiry(mut self, message_expiry: impl Into<Option<Duration>>) -> Self {
+        self.message_expiry = message_expiry.into();
+        self
+    }
+
+    pub fn properties(mut self, properties: MessageProperties) -> Self {
+        self.properties = properties;
+        self
+    }
 }
 
 #[derive(Clone)]
 pub struct Client {
-    pub mqtt: AsyncClient,
+    /// The MQTT client, [`MqttClient::as_v3`] provides the underlying client of MQTT v3.1.1.
+    pub mqtt: MqttClient,
 
     pub(crate) base_topic: String,
+
+    /// The origin added to announcements which don't have one.
+    pub(crate) origin: Option<Origin>,
+
+    pub(crate) announce_options: PublishOptions,
+    pub(crate) state_options: PublishOptions,
+
+    /// The pending cleanup of stale discovery messages, shared with the connector.
+    pub(crate) cleanup: Arc<Mutex<Option<Cleanup>>>,
+
+    /// The last announcements and states, replayed by the connector.
+    pub(crate) cache: Arc<Mutex<Cache>>,
+}
+
+/// Cleanup of the discovery messages of a node, which are not owned by the agent anymore.
+#[derive(Debug)]
+pub(crate) struct Cleanup {
+    prefix: String,
+    node_id: String,
+    owned: HashSet<String>,
+    /// The time after which the retained messages should have been received.
+    deadline: Instant,
+}
+
+impl Cleanup {
+    /// The topic filter for the discovery messages of the node.
+    fn filter(&self) -> String {
+        format!("{}+/{}/+/config", self.prefix, self.node_id)
+    }
+
+    /// Get the component of a config topic of the node, if the topic matches the filter.
+    fn component<'t>(&self, topic: &'t str) -> Option<&'t str> {
+        let rest = topic.strip_prefix(&self.prefix)?;
+
+        match rest.split('/').collect::<Vec<_>>()[..] {
+            [component, node_id, _object_id, "config"] if node_id == self.node_id => {
+                Some(component)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if the topic is a config topic of the node.
+    fn matches(&self, topic: &str) -> bool {
+        self.component(topic).is_some()
+    }
+
+    /// Check if the topic is a config topic of the node, which is not owned anymore.
+    ///
+    /// Device based discovery messages are never considered stale.
+    fn is_stale(&self, topic: &str) -> bool {
+        self.component(topic)
+            .is_some_and(|component| component != "device" && !self.owned.contains(topic))
+    }
 }
 
 impl Client {
+    pub(crate) fn new(mqtt: MqttClient, base_topic: String, origin: Option<Origin>) -> Self {
+        Self {
+            mqtt,
+            base_topic,
+            origin,
+            announce_options: Default::default(),
+            state_options: Default::default(),
+            cleanup: Default::default(),
+            cache: Default::default(),
+        }
+    }
+
+    pub(crate) fn announce_options(mut self, options: PublishOptions) -> Self {
+        self.announce_options = options;
+        self
+    }
+
+    pub(crate) fn state_options(mut self, options: PublishOptions) -> Self {
+        self.state_options = options;
+        self
+    }
+
     pub async fn update_state(
         &self,
         topic: impl Into<String>,
         payload: impl Into<Vec<u8>>,
+    ) -> Result<(), ClientError> {
+        self.update_state_with(topic, payload, &self.state_options)
+            .await
+    }
+
+    /// Update the state, using the provided publish options.
+    pub async fn update_state_with(
+        &self,
+        topic: impl Into<String>,
+        payload: impl Into<Vec<u8>>,
+        options: &PublishOptions,
     ) -> Result<(), ClientError> {
         let topic = topic.into();
+        let payload = payload.into();
         log::info!("Update state on {topic}");
 
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.state(&topic, &payload, options);
+        }
+
         self.mqtt
-            .try_publish(topic, QoS::AtLeastOnce, false, payload.into())
+            .try_publish(topic, payload, options)
             .inspect_err(|err| {
                 log::warn!("failed to publish state: {err}");
             })?;
@@ -34,16 +203,350 @@ impl Client {
         Ok(())
     }
 
+    /// Publish a typed state, encoded as declared by the discovery information of the entity.
+    pub async fn publish_state(
+        &self,
+        entity: &Discovery,
+        value: impl Into<StateValue>,
+    ) -> Result<(), ClientError> {
+        let topic = entity
+            .state_topic
+            .as_deref()
+            .ok_or(ClientError::MissingTopic("state_topic"))?;
+
+        self.update_state(topic, value.into().encode(entity)?).await
+    }
+
+    /// Update the additional attributes of an entity, serialized as JSON object.
+    ///
+    /// The topic must be the `json_attributes_topic` of the entity.
+    pub async fn update_attributes(
+        &self,
+        topic: impl Into<String>,
+        attributes: &impl serde::Serialize,
+    ) -> Result<(), ClientError> {
+        self.update_state(topic, serde_json::to_vec(attributes)?)
+            .await
+    }
+
     pub async fn announce(&self, id: &DeviceId, discovery: &Discovery) -> Result<(), ClientError> {
-        let topic = format!("{}/{}", self.base_topic, id.config_topic());
+        self.announce_with(id, discovery, KeyFormat::Full, &self.announce_options)
+            .await
+    }
+
+    /// Announce a discovery message, using the provided key format and publish options.
+    ///
+    /// Use [`KeyFormat::Abbreviated`] to reduce the size of the payload.
+    pub async fn announce_with(
+        &self,
+        id: &DeviceId,
+        discovery: &Discovery,
+        format: KeyFormat,
+        options: &PublishOptions,
+    ) -> Result<(), ClientError> {
+        let (topic, payload) = self.announcement(id, discovery, format)?;
         log::info!("announce {id} on {topic}: {discovery:?}", id = id.id);
 
+        self.publish_announcement(topic, payload, options).await
+    }
+
+    /// Add an announcement to the cache, without publishing it.
+    ///
+    /// The connector publishes it with the next replay, after connecting or Home Assistant
+    /// restarting.
+    pub(crate) fn register(&self, id: &DeviceId, discovery: &Discovery) -> Result<(), ClientError> {
+        let (topic, payload) = self.announcement(id, discovery, KeyFormat::Full)?;
+        log::debug!("register {id} on {topic}", id = id.id);
+
+        self.own(&topic);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.registered(&topic, &payload, &self.announce_options);
+        }
+
+        Ok(())
+    }
+
+    /// The topic and payload of an announcement.
+    fn announcement(
+        &self,
+        id: &DeviceId,
+        discovery: &Discovery,
+        format: KeyFormat,
+    ) -> Result<(String, Vec<u8>), ClientError> {
+        let discovery = match (&discovery.origin, &self.origin) {
+            (None, Some(origin)) => Cow::Owned(Discovery {
+                origin: Some(origin.clone()),
+                ..discovery.clone()
+            }),
+            _ => Cow::Borrowed(discovery),
+        };
+
+        Ok((
+            format!("{}/{}", self.base_topic, id.config_topic()),
+            serde_json::to_vec(&discovery.to_value(format)?)?,
+        ))
+    }
+
+    async fn publish_announcement(
+        &self,
+        topic: String,
+        payload: Vec<u8>,
+        options: &PublishOptions,
+    ) -> Result<(), ClientError> {
+        self.own(&topic);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.announced(&topic, &payload, options);
+        }
+
+        self.mqtt.publish(topic, payload, options).await?;
+
+        Ok(())
+    }
+
+    /// Protect an announcement from a pending cleanup.
+    fn own(&self, topic: &str) {
+        if let Ok(mut cleanup) = self.cleanup.lock() {
+            if let Some(cleanup) = cleanup.as_mut() {
+                cleanup.owned.insert(topic.to_string());
+            }
+        }
+    }
+
+    /// Remove all retained discovery messages of a node, which are not in the set of owned IDs.
+    ///
+    /// This subscribes to all discovery messages of the node, and clears the retained ones which
+    /// are not owned. Only component based discovery messages of the node are considered, so
+    /// entities of other nodes or integrations are never removed. Entities announced while the
+    /// cleanup is pending are considered owned.
+    ///
+    /// The cleanup ends after a few seconds, unsubscribing from the discovery messages again.
+    ///
+    /// This should be called once connected, as it requires a subscription.
+    pub async fn cleanup<I>(&self, node_id: impl Into<String>, owned: I) -> Result<(), ClientError>
+    where
+        I: IntoIterator<Item = DeviceId>,
+    {
+        let node_id = node_id.into();
+        if node_id.is_empty() || node_id.contains(['+', '#', '/']) {
+            return Err(ClientError::InvalidNodeId(node_id));
+        }
+
+        let cleanup = Cleanup {
+            prefix: format!("{}/", self.base_topic),
+            node_id,
+            owned: owned
+                .into_iter()
+                .map(|id| format!("{}/{}", self.base_topic, id.config_topic()))
+                .collect(),
+            deadline: Instant::now() + CLEANUP_TIMEOUT,
+        };
+        let filter = cleanup.filter();
+
+        let previous = match self.cleanup.lock() {
+            Ok(mut current) => current.replace(cleanup),
+            Err(_) => None,
+        };
+        if let Some(previous) = previous.filter(|previous| previous.filter() != filter) {
+            self.mqtt.unsubscribe(previous.filter()).await?;
+        }
+
+        self.subscribe(filter, QoS::AtLeastOnce).await?;
+
+        let client = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(CLEANUP_TIMEOUT).await;
+            if let Err(err) = client.finish_cleanup().await {
+                log::warn!("Failed to finish cleanup: {err}");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// End the pending cleanup, if it passed its deadline.
+    async fn finish_cleanup(&self) -> Result<(), ClientError> {
+        let cleanup = match self.cleanup.lock() {
+            Ok(mut cleanup)
+                if cleanup
+                    .as_ref()
+                    .is_some_and(|c| c.deadline <= Instant::now()) =>
+            {
+                cleanup.take()
+            }
+            _ => None,
+        };
+
+        if let Some(cleanup) = cleanup {
+            log::info!("Finished cleanup of node {}", cleanup.node_id);
+            self.mqtt.unsubscribe(cleanup.filter()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle a message for a pending cleanup, returns `true` if the message was consumed.
+    pub(crate) fn handle_cleanup(&self, publish: &Message) -> bool {
+        let Ok(cleanup) = self.cleanup.lock() else {
+            return false;
+        };
+        let Some(cleanup) = cleanup.as_ref() else {
+            return false;
+        };
+
+        if !cleanup.matches(&publish.topic) {
+            return false;
+        }
+
+        // only retained messages are the ones which existed before, and empty ones are deleted
+        if publish.retain && !publish.payload.is_empty() && cleanup.is_stale(&publish.topic) {
+            log::info!("Removing stale discovery message: {}", publish.topic);
+            if let Err(err) = self.mqtt.try_publish(
+                publish.topic.clone(),
+                Vec::new(),
+                &PublishOptions::new().retain(true),
+            ) {
+                log::warn!("Failed to remove stale discovery message: {err}");
+            }
+        }
+
+        true
+    }
+
+    /// Remove a previously announced entity, by clearing its retained discovery message.
+    pub async fn unannounce(&self, id: &DeviceId) -> Result<(), ClientError> {
+        self.clear_retained(format!("{}/{}", self.base_topic, id.config_topic()))
+            .await
+    }
+
+    /// Announce a device, with all of its components, using a single message.
+    ///
+    /// Other than with [`Client::announce`], the message is always retained.
+    pub async fn announce_device(
+        &self,
+        id: &DeviceDiscoveryId,
+        discovery: &DeviceDiscovery,
+    ) -> Result<(), ClientError> {
+        let options = self.announce_options.clone().retain(true);
+        self.announce_device_with(id, discovery, KeyFormat::Full, &options)
+            .await
+    }
+
+    /// Announce a device, using the provided key format and publish options.
+    pub async fn announce_device_with(
+        &self,
+        id: &DeviceDiscoveryId,
+        discovery: &DeviceDiscovery,
+        format: KeyFormat,
+        options: &PublishOptions,
+    ) -> Result<(), ClientError> {
+        let topic = format!("{}/{}", self.base_topic, id.config_topic());
+        log::info!(
+            "announce device {id} with {components} components on {topic}",
+            id = id.id,
+            components = discovery.components.len()
+        );
+
+        self.publish_announcement(
+            topic,
+            serde_json::to_vec(&discovery.to_value(format)?)?,
+            options,
+        )
+        .await
+    }
+
+    /// Remove a previously announced device, and all of its components.
+    pub async fn unannounce_device(&self, id: &DeviceDiscoveryId) -> Result<(), ClientError> {
+        self.clear_retained(format!("{}/{}", self.base_topic, id.config_topic()))
+            .await
+    }
+
+    /// Clear a retained message, by publishing an empty retained payload.
+    async fn clear_retained(&self, topic: String) -> Result<(), ClientError> {
+        log::info!("clear retained message on {topic}");
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.unannounced(&topic);
+        }
+
+        self.mqtt
+            .publish(topic, Vec::new(), &PublishOptions::new().retain(true))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mark all cached announcements and states as pending, so that they get replayed.
+    pub(crate) fn invalidate(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.invalidate();
+        }
+    }
+
+    /// Publish all pending announcements and states again.
+    ///
+    /// Messages which were published again since the cache was invalidated are skipped.
+    pub(crate) async fn replay(&self) {
+        let messages = match self.cache.lock() {
+            Ok(cache) => cache.pending(),
+            Err(_) => return,
+        };
+        if messages.is_empty() {
+            return;
+        }
+
+        log::info!("Replaying {} announcements and states", messages.len());
+
+        for (topic, message) in messages {
+            match self
+                .mqtt
+                .publish(&topic, message.payload, &message.options)
+                .await
+            {
+                Ok(()) => {
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.replayed(&topic);
+                    }
+                }
+                Err(err) => log::warn!("Failed to replay message on {topic}: {err}"),
+            }
+        }
+    }
+
+    /// Fire an event of an event entity, on its state topic, using the state options.
+    ///
+    /// The event type must be one of the announced event types of the entity.
+    pub async fn fire_event(&self, entity: &Discovery, event: &Event) -> Result<(), ClientError> {
+        let topic = entity
+            .state_topic
+            .clone()
+            .ok_or(ClientError::MissingTopic("state_topic"))?;
+
+        if !entity.event_types.contains(&event.event_type) {
+            return Err(ClientError::UnknownEventType(event.event_type.clone()));
+        }
+
+        log::info!("Fire event {} on {topic}", event.event_type);
+
+        self.mqtt
+            .publish(topic, serde_json::to_vec(event)?, &self.state_options)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fire a device trigger, using its topic and payload, and the state options.
+    pub async fn fire_trigger(&self, trigger: &Discovery) -> Result<(), ClientError> {
+        let topic = trigger
+            .topic
+            .clone()
+            .ok_or(ClientError::MissingTopic("topic"))?;
+        log::info!("Fire trigger on {topic}");
+
         self.mqtt
             .publish(
                 topic,
-                QoS::AtLeastOnce,
-                false,
-                serde_json::to_vec(discovery)?,
+                trigger.payload.clone().unwrap_or_default(),
+                &self.state_options,
             )
             .await?;
 
@@ -58,3 +561,115 @@ impl Client {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{Component, EventDiscovery, SensorDiscovery};
+    use rumqttc::{AsyncClient, MqttOptions};
+
+    fn client() -> (Client, rumqttc::EventLoop) {
+        let (mqtt, eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        (
+            Client::new(MqttClient::V3(mqtt), "homeassistant".into(), None),
+            eventloop,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fire_event() {
+        let (client, _eventloop) = client();
+        let discovery = EventDiscovery::new("button/event", ["press", "hold"]).build();
+
+        client
+            .fire_event(&discovery, &Event::new("press"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.fire_event(&discovery, &Event::new("release")).await,
+            Err(ClientError::UnknownEventType(event_type)) if event_type == "release"
+        ));
+    }
+
+    #[test]
+    fn test_cleanup_stale() {
+        let cleanup = Cleanup {
+            prefix: "homeassistant/".into(),
+            node_id: "agent".into(),
+            owned: HashSet::from([format!(
+                "homeassistant/{}",
+                DeviceId::with_node_id("sensor-1", Component::Sensor, "agent").config_topic()
+            )]),
+            deadline: Instant::now(),
+        };
+
+        assert!(!cleanup.is_stale("homeassistant/sensor/agent/sensor-1/config"));
+        assert!(cleanup.is_stale("homeassistant/sensor/agent/sensor-2/config"));
+        assert!(cleanup.is_stale("homeassistant/switch/agent/sensor-1/config"));
+        assert!(!cleanup.is_stale("homeassistant/sensor/other/sensor-2/config"));
+        assert!(!cleanup.is_stale("homeassistant/device/agent/device-1/config"));
+        assert!(!cleanup.is_stale("homeassistant/sensor/sensor-2/config"));
+        assert!(!cleanup.is_stale("other/sensor/agent/sensor-2/config"));
+    }
+
+    fn retained(topic: &str) -> Message {
+        Message {
+            topic: topic.into(),
+            payload: bytes::Bytes::from_static(b"{}"),
+            qos: QoS::AtLeastOnce,
+            retain: true,
+            properties: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cleanup() {
+        let (client, _eventloop) = client();
+        let sensor = |id| DeviceId::with_node_id(id, Component::Sensor, "agent");
+
+        client.cleanup("agent", [sensor("sensor-1")]).await.unwrap();
+
+        // other nodes and topics are left to the handler
+        assert!(!client.handle_cleanup(&retained("homeassistant/sensor/other/sensor-2/config")));
+        assert!(!client.handle_cleanup(&retained("homeassistant/status")));
+        assert!(!client.handle_cleanup(&retained("homeassistant/sensor/agent/state")));
+        assert!(client.handle_cleanup(&retained("homeassistant/sensor/agent/sensor-2/config")));
+
+        // entities added while the cleanup is pending are owned
+        let discovery = SensorDiscovery::new("sensor/state").build();
+        client
+            .announce(&sensor("sensor-3"), &discovery)
+            .await
+            .unwrap();
+        let topic = format!("homeassistant/{}", sensor("sensor-3").config_topic());
+        assert!(client.handle_cleanup(&retained(&topic)));
+        assert!(!client
+            .cleanup
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .is_stale(&topic));
+
+        // the cleanup is cleared once it passed its deadline
+        client.finish_cleanup().await.unwrap();
+        assert!(client.cleanup.lock().unwrap().is_some());
+        client.cleanup.lock().unwrap().as_mut().unwrap().deadline = Instant::now();
+        client.finish_cleanup().await.unwrap();
+        assert!(client.cleanup.lock().unwrap().is_none());
+        assert!(!client.handle_cleanup(&retained(&topic)));
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_invalid_node_id() {
+        let (client, _eventloop) = client();
+
+        for node_id in ["", "+", "agent/#", "other/agent"] {
+            assert!(matches!(
+                client.cleanup(node_id, []).await,
+                Err(ClientError::InvalidNodeId(id)) if id == node_id
+            ));
+        }
+        assert!(client.cleanup.lock().unwrap().is_none());
+    }
+}
@@ -1,7 +1,33 @@
+use crate::connector::{MqttClientError, MqttConnectionError};
+
+/// An error running the [`crate::connector::Connector`].
 #[derive(Debug, thiserror::Error)]
 pub enum Error<H> {
     #[error(transparent)]
     Handler(H),
-    #[error("MQTT client error: {0}")]
-    Client(#[from] rumqttc::v5::ClientError),
+    /// Subscribing failed, and reconnecting gave up.
+    #[error("failed to subscribe")]
+    Subscribe(#[source] MqttClientError),
+    /// Publishing failed, and reconnecting gave up.
+    #[error("failed to publish")]
+    Publish(#[source] MqttClientError),
+    /// Disconnecting, after failing to handle a message, failed and reconnecting gave up.
+    #[error("failed to disconnect")]
+    Disconnect(#[source] MqttClientError),
+    /// The broker refused the connection, and [`ConnectorOptions::fail_on_refusal`] is enabled.
+    ///
+    /// [`ConnectorOptions::fail_on_refusal`]: crate::connector::ConnectorOptions::fail_on_refusal
+    #[error("connection error")]
+    Connection(#[source] MqttConnectionError),
+    #[error("giving up after {attempts} failed connection attempts")]
+    ReconnectFailed {
+        attempts: u32,
+        #[source]
+        source: MqttConnectionError,
+    },
+    /// Shutting down didn't complete within [`ConnectorOptions::shutdown_timeout`].
+    ///
+    /// [`ConnectorOptions::shutdown_timeout`]: crate::connector::ConnectorOptions::shutdown_timeout
+    #[error("timed out waiting for a clean shutdown")]
+    ShutdownTimeout,
 }
@@ -1,18 +1,23 @@
+mod backoff;
+mod cache;
 mod client;
 mod error;
+mod mqtt;
 mod options;
 
+pub(crate) use backoff::*;
+pub(crate) use cache::*;
 pub use client::*;
 pub use error::*;
+pub use mqtt::*;
 pub use options::*;
 
-use crate::connector::Error;
+use crate::model::Origin;
 use bytes::Bytes;
 use rand::{distributions::Alphanumeric, Rng};
-use rumqttc::{
-    AsyncClient, Event, Incoming, LastWill, MqttOptions, QoS, TlsConfiguration, Transport,
-};
-use std::{future::Future, time::Duration};
+use rumqttc::QoS;
+use std::{future::Future, pin::pin, time::Duration};
+use tokio::task::JoinHandle;
 
 fn random_client_id() -> String {
     rand::thread_rng()
@@ -22,17 +27,80 @@ fn random_client_id() -> String {
         .collect()
 }
 
+/// A failure of the connection, which is handled by reconnecting.
+#[derive(Debug, thiserror::Error)]
+enum Failure {
+    #[error("connection failed: {0}")]
+    Connection(MqttConnectionError),
+    #[error("failed to subscribe: {0}")]
+    Subscribe(MqttClientError),
+    #[error("failed to publish: {0}")]
+    Publish(MqttClientError),
+    #[error("failed to disconnect: {0}")]
+    Disconnect(MqttClientError),
+}
+
+impl Failure {
+    /// The error to return, once reconnecting gave up.
+    fn into_error<H>(self, attempts: u32) -> Error<H> {
+        match self {
+            Self::Connection(source) => Error::ReconnectFailed { attempts, source },
+            Self::Subscribe(err) => Error::Subscribe(err),
+            Self::Publish(err) => Error::Publish(err),
+            Self::Disconnect(err) => Error::Disconnect(err),
+        }
+    }
+}
+
+/// The replay of the cached announcements and states, with at most one pending at a time.
+#[derive(Default)]
+struct Replay(Option<JoinHandle<()>>);
+
+impl Replay {
+    /// Replay the pending messages, after a random delay of up to `jitter`.
+    ///
+    /// This replaces a replay which is still pending.
+    fn schedule(&mut self, client: &Client, jitter: Duration) {
+        self.cancel();
+
+        let delay =
+            Duration::from_millis(rand::thread_rng().gen_range(0..=jitter.as_millis() as u64));
+        let client = client.clone();
+
+        self.0 = Some(tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            client.replay().await;
+        }));
+    }
+
+    fn cancel(&mut self) {
+        if let Some(replay) = self.0.take() {
+            replay.abort();
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
 pub trait ConnectorHandler {
     type Error: std::error::Error + Send + Sync;
 
     /// called when the connection state changes.
     ///
     /// NOTE: it may be that this method gets called with the same state multiple times.
+    ///
+    /// Once connected, announcements and states published through the [`Client`] are replayed
+    /// automatically, unless they were published again from here.
     fn connected(&mut self, state: bool) -> impl Future<Output = Result<(), Self::Error>>;
 
     /// Called then a restart of Home Assistant has been detected
     ///
-    /// When Home Assistant is restarted, it is necessary to re-announce devices.
+    /// When Home Assistant is restarted, it is necessary to re-announce devices. Announcements
+    /// and states published through the [`Client`] are replayed automatically.
     fn restarted(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
 
     /// A message received on a topic.
@@ -43,6 +111,40 @@ pub trait ConnectorHandler {
         topic: String,
         payload: Bytes,
     ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// A message received on a topic, with its properties.
+    ///
+    /// The properties are only available when using MQTT v5. By default, this forwards to
+    /// [`Self::message`], dropping the properties.
+    fn message_with_properties(
+        &mut self,
+        topic: String,
+        payload: Bytes,
+        properties: Option<MessageProperties>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        let _ = properties;
+        self.message(topic, payload)
+    }
+
+    /// Called when the broker rejected a subscription, with the reasons of the failed topic
+    /// filters.
+    ///
+    /// By default, this does nothing, as the connector already logs the failure.
+    fn subscribe_failed(
+        &mut self,
+        reasons: Vec<SubscribeFailure>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        let _ = reasons;
+        async { Ok(()) }
+    }
+
+    /// Called when shutting down, before disconnecting.
+    ///
+    /// This can be used to mark entities as unavailable. Messages published from here are
+    /// still sent, before disconnecting.
+    fn shutdown(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        async { Ok(()) }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -67,6 +169,9 @@ where
     options: ConnectorOptions,
     handler: F,
     availability: Option<AvailabilityOptions>,
+    origin: Option<Origin>,
+    announce_options: PublishOptions,
+    state_options: PublishOptions,
 }
 
 impl<F, H> Connector<F, H>
@@ -79,6 +184,9 @@ where
             options,
             handler,
             availability: None,
+            origin: None,
+            announce_options: Default::default(),
+            state_options: Default::default(),
         }
     }
 
@@ -87,7 +195,44 @@ where
         self
     }
 
+    /// Set the origin, which is added to all announcements which don't have one.
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Set the default options of the client for announcing, defaults to QoS 1 without
+    /// retaining.
+    pub fn announce_options(mut self, options: PublishOptions) -> Self {
+        self.announce_options = options;
+        self
+    }
+
+    /// Set the default options of the client for updating states and firing events, defaults
+    /// to QoS 1 without retaining.
+    pub fn state_options(mut self, options: PublishOptions) -> Self {
+        self.state_options = options;
+        self
+    }
+
+    /// Run the connector, until the connection fails permanently.
     pub async fn run(self) -> Result<(), Error<H::Error>> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Run the connector, until `shutdown` completes.
+    ///
+    /// When shutting down, the availability is set to `offline`, the handler gets notified, and
+    /// the connection is closed after outstanding messages have been acknowledged. All of this
+    /// is limited by the shutdown timeout, returning [`Error::ShutdownTimeout`] when exceeded.
+    /// This doesn't trigger the last will.
+    pub async fn run_until(
+        self,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error<H::Error>> {
+        let mut shutdown = pin!(shutdown);
+        let mut backoff = Backoff::new(&self.options);
+
         let base = self
             .options
             .topic_base
@@ -100,104 +245,307 @@ where
             .port
             .unwrap_or(if self.options.disable_tls { 1883 } else { 8883 });
 
-        let mut mqttoptions = MqttOptions::new(client_id, self.options.host, port);
-        mqttoptions.set_keep_alive(self.options.keep_alive);
-
-        if !self.options.disable_tls {
-            mqttoptions.set_transport(Transport::Tls(TlsConfiguration::Native));
-        }
-
-        log::debug!("Options: {mqttoptions:#?}");
-
-        if let Some(username) = self.options.username {
-            mqttoptions.set_credentials(username, self.options.password.unwrap_or_default());
-        }
-
         if let Some(availability) = &self.availability {
             log::info!("Using availability topic on: {}", availability.topic);
-            mqttoptions.set_last_will(LastWill {
-                topic: availability.topic.clone(),
-                message: Bytes::from_static(b"offline"),
-                qos: QoS::AtLeastOnce,
-                retain: true,
-            });
         }
 
-        let (client, mut eventloop) = AsyncClient::new(mqttoptions, 128);
+        let (client, mut eventloop) = MqttConnectOptions {
+            version: self.options.mqtt_version,
+            client_id,
+            host: self.options.host,
+            port,
+            keep_alive: self.options.keep_alive,
+            tls: !self.options.disable_tls,
+            credentials: self
+                .options
+                .username
+                .map(|username| (username, self.options.password.unwrap_or_default())),
+            last_will: self
+                .availability
+                .as_ref()
+                .map(|availability| availability.topic.clone()),
+        }
+        .create(128);
 
-        let mut handler = (self.handler)(Client {
-            base_topic: base.clone(),
-            mqtt: client.clone(),
-        });
+        let agent = Client::new(client.clone(), base.clone(), self.origin)
+            .announce_options(self.announce_options)
+            .state_options(self.state_options);
+        let mut handler = (self.handler)(agent.clone());
 
         let status_topic = format!("{base}/status");
+        let availability_options = PublishOptions::new().retain(true);
+        let mut replay = Replay::default();
 
         loop {
-            match eventloop.poll().await {
-                Ok(Event::Incoming(Incoming::ConnAck(_))) => {
-                    log::info!("Connected");
-                    if let Err(err) = client.try_subscribe(&status_topic, QoS::AtLeastOnce) {
-                        log::warn!("Failed to subscribe to status the topic: {err}");
-                        if let Err(err) = client.try_disconnect() {
-                            panic!("Failed to disconnect after error: {err}");
-                        }
-                    }
+            let event = tokio::select! {
+                event = eventloop.poll() => event,
+                _ = &mut shutdown => break,
+            };
 
+            let failure = match event {
+                Ok(MqttEvent::Connected) => {
+                    log::info!("Connected");
+                    agent.invalidate();
                     handler.connected(true).await.map_err(Error::Handler)?;
+                    replay.schedule(&agent, self.options.replay_jitter);
 
-                    if let Some(availability) = &self.availability {
-                        if let Err(err) = client.try_publish(
-                            &availability.topic,
-                            QoS::AtLeastOnce,
-                            true,
-                            b"online",
-                        ) {
-                            log::warn!("Failed to announce availability: {err}");
-                            if let Err(err) = client.try_disconnect() {
-                                panic!("Failed to disconnect after error: {err}");
-                            }
-                        }
+                    let failure = match client.try_subscribe(&status_topic, QoS::AtLeastOnce) {
+                        Err(err) => Some(Failure::Subscribe(err)),
+                        Ok(()) => self
+                            .availability
+                            .as_ref()
+                            .and_then(|availability| {
+                                client
+                                    .try_publish(
+                                        &availability.topic,
+                                        "online",
+                                        &availability_options,
+                                    )
+                                    .err()
+                            })
+                            .map(Failure::Publish),
+                    };
+                    // only a complete connection resets the backoff
+                    if failure.is_none() {
+                        backoff.reset();
                     }
+                    failure
                 }
-                Ok(Event::Incoming(Incoming::Disconnect)) => {
+                Ok(MqttEvent::Disconnected) => {
                     log::info!("Disconnected");
+                    replay.cancel();
                     handler.connected(false).await.map_err(Error::Handler)?;
+                    None
                 }
-                Ok(Event::Incoming(Incoming::Publish(publish))) => {
+                Ok(MqttEvent::Message(publish)) => {
                     log::info!("Received: {publish:?}");
                     if publish.topic == status_topic {
                         let payload = String::from_utf8_lossy(&publish.payload);
                         log::info!("Payload: {}", payload);
                         if payload == "online" {
+                            agent.invalidate();
                             handler.restarted().await.map_err(Error::Handler)?;
+                            replay.schedule(&agent, self.options.replay_jitter);
                         }
+                        None
+                    } else if agent.handle_cleanup(&publish) {
+                        log::debug!("Handled discovery message for cleanup: {}", publish.topic);
+                        None
                     } else {
                         let topic = publish.topic;
                         let payload = publish.payload;
                         log::info!("Message published: {topic} (len: {})", payload.len());
 
-                        if let Err(err) = handler.message(topic.to_string(), payload).await {
-                            if publish.qos != QoS::AtMostOnce {
-                                // we can't ignore this
+                        match handler
+                            .message_with_properties(topic, payload, publish.properties)
+                            .await
+                        {
+                            Err(err) if publish.qos != QoS::AtMostOnce => {
+                                // we can't ignore this, reconnect to start over
                                 log::warn!("Failed to process message: {err}");
-                                if let Err(err) = client.try_disconnect() {
-                                    panic!("Failed to disconnect after error: {err}");
-                                }
-                            } else {
+                                client.try_disconnect().err().map(Failure::Disconnect)
+                            }
+                            Err(err) => {
                                 log::info!(
                                     "Failed to process message: {err} … ignoring due to QoS"
                                 );
+                                None
                             }
+                            Ok(()) => None,
                         }
                     }
                 }
-                Ok(_) => {}
-                Err(err) => {
-                    log::warn!("Connection failed: {err}");
-                    handler.connected(false).await.map_err(Error::Handler)?;
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(MqttEvent::SubscribeFailed(reasons)) => {
+                    log::warn!(
+                        "Failed to subscribe: {}",
+                        reasons
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    handler
+                        .subscribe_failed(reasons)
+                        .await
+                        .map_err(Error::Handler)?;
+                    None
+                }
+                Ok(_) => None,
+                Err(err) => Some(Failure::Connection(err)),
+            };
+
+            let Some(failure) = failure else {
+                continue;
+            };
+
+            log::warn!("{failure}");
+            replay.cancel();
+            handler.connected(false).await.map_err(Error::Handler)?;
+
+            let failure = match failure {
+                Failure::Connection(err) if self.options.fail_on_refusal && err.is_refused() => {
+                    return Err(Error::Connection(err));
+                }
+                failure => failure,
+            };
+
+            let Some(delay) = backoff.next() else {
+                return Err(failure.into_error(backoff.attempts()));
+            };
+            // drop the connection, so that the next poll reconnects
+            eventloop.reconnect();
+
+            log::info!(
+                "Reconnecting in {} (attempt {})",
+                humantime::format_duration(delay),
+                backoff.attempts() + 1
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+
+        log::info!("Shutting down");
+        replay.cancel();
+
+        let shutdown = async {
+            if let Some(availability) = &self.availability {
+                if let Err(err) =
+                    client.try_publish(&availability.topic, "offline", &availability_options)
+                {
+                    log::warn!("Failed to announce availability: {err}");
+                }
+            }
+
+            let result = drive(&mut eventloop, handler.shutdown()).await;
+            disconnect(&client, &mut eventloop).await;
+            result
+        };
+
+        match tokio::time::timeout(self.options.shutdown_timeout, shutdown).await {
+            Ok(result) => result.map_err(Error::Handler),
+            Err(_) => Err(Error::ShutdownTimeout),
+        }
+    }
+}
+
+/// Await a future, while polling the event loop so that its requests get processed.
+///
+/// The event loop is no longer polled once the connection failed.
+async fn drive<T>(eventloop: &mut MqttEventLoop, future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut connected = true;
+
+    loop {
+        tokio::select! {
+            result = &mut future => return result,
+            event = eventloop.poll(), if connected => {
+                if let Err(err) = event {
+                    log::info!("Connection closed while shutting down: {err}");
+                    connected = false;
                 }
             }
         }
     }
 }
+
+/// Flush all pending messages, wait for them to be acknowledged, and disconnect.
+async fn disconnect(client: &MqttClient, eventloop: &mut MqttEventLoop) {
+    /// The time without activity, after which all pending requests are considered sent.
+    const IDLE: Duration = Duration::from_millis(100);
+
+    loop {
+        match tokio::time::timeout(IDLE, eventloop.poll()).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => {
+                log::info!("Connection closed while shutting down: {err}");
+                return;
+            }
+            Err(_) if eventloop.inflight() == 0 => break,
+            Err(_) => {}
+        }
+    }
+
+    if let Err(err) = client.try_disconnect() {
+        log::warn!("Failed to disconnect: {err}");
+        return;
+    }
+
+    loop {
+        match eventloop.poll().await {
+            Ok(MqttEvent::DisconnectSent) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_source() {
+        let err =
+            Error::<std::io::Error>::Connection(rumqttc::ConnectionError::NetworkTimeout.into());
+        assert_eq!(err.to_string(), "connection error");
+        assert_eq!(
+            err.source().map(ToString::to_string).as_deref(),
+            Some("Network timeout")
+        );
+    }
+
+    #[test]
+    fn test_failure_error() {
+        let err =
+            rumqttc::ClientError::TryRequest(rumqttc::Request::Disconnect(rumqttc::Disconnect));
+        assert!(matches!(
+            Failure::Subscribe(err.into()).into_error::<std::io::Error>(3),
+            Error::Subscribe(_)
+        ));
+        assert!(matches!(
+            Failure::Connection(rumqttc::ConnectionError::NetworkTimeout.into())
+                .into_error::<std::io::Error>(3),
+            Error::ReconnectFailed { attempts: 3, .. }
+        ));
+    }
+
+    struct Stuck;
+
+    impl ConnectorHandler for Stuck {
+        type Error = std::io::Error;
+
+        async fn connected(&mut self, _: bool) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn restarted(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn message(&mut self, _: String, _: Bytes) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> Result<(), Self::Error> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout() {
+        let options: ConnectorOptions = serde_json::from_value(serde_json::json!({
+            "host": "127.0.0.1",
+            "port": 1,
+            "disableTls": true,
+            "shutdownTimeout": "10ms",
+        }))
+        .unwrap();
+
+        let result = Connector::new(options, |_| Stuck)
+            .run_until(std::future::ready(()))
+            .await;
+        assert!(matches!(result, Err(Error::ShutdownTimeout)));
+    }
+}
@@ -0,0 +1,580 @@
+//! A common interface for the MQTT v3.1.1 and v5 clients.
+
+use crate::connector::PublishOptions;
+use bytes::Bytes;
+use rumqttc::{
+    v5::{
+        self,
+        mqttbytes::v5::{Packet, PublishProperties},
+    },
+    ConnectReturnCode, Event, Incoming, Outgoing, QoS, SubscribeReasonCode, TlsConfiguration,
+    Transport,
+};
+use std::time::Duration;
+
+/// The version of the MQTT protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MqttVersion {
+    /// MQTT 3.1.1
+    #[default]
+    V3,
+    /// MQTT 5
+    V5,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MqttClientError {
+    #[error(transparent)]
+    V3(#[from] rumqttc::ClientError),
+    #[error(transparent)]
+    V5(Box<v5::ClientError>),
+}
+
+impl From<v5::ClientError> for MqttClientError {
+    fn from(value: v5::ClientError) -> Self {
+        Self::V5(Box::new(value))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MqttConnectionError {
+    #[error(transparent)]
+    V3(#[from] rumqttc::ConnectionError),
+    #[error(transparent)]
+    V5(#[from] v5::ConnectionError),
+}
+
+impl MqttConnectionError {
+    /// Check if the broker refused the connection, in a way which reconnecting won't fix.
+    pub(crate) fn is_refused(&self) -> bool {
+        use v5::mqttbytes::v5::ConnectReturnCode as V5;
+
+        match self {
+            Self::V3(err) => matches!(
+                err,
+                rumqttc::ConnectionError::ConnectionRefused(
+                    ConnectReturnCode::RefusedProtocolVersion
+                        | ConnectReturnCode::BadClientId
+                        | ConnectReturnCode::BadUserNamePassword
+                        | ConnectReturnCode::NotAuthorized
+                )
+            ),
+            Self::V5(err) => matches!(
+                err,
+                v5::ConnectionError::ConnectionRefused(
+                    V5::RefusedProtocolVersion
+                        | V5::UnsupportedProtocolVersion
+                        | V5::BadClientId
+                        | V5::ClientIdentifierNotValid
+                        | V5::BadUserNamePassword
+                        | V5::BadAuthenticationMethod
+                        | V5::NotAuthorized
+                        | V5::Banned
+                )
+            ),
+        }
+    }
+}
+
+/// Additional properties of a message, only supported by MQTT v5.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageProperties {
+    pub content_type: Option<String>,
+    pub response_topic: Option<String>,
+    pub correlation_data: Option<Bytes>,
+    pub user_properties: Vec<(String, String)>,
+}
+
+impl MessageProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn response_topic(mut self, response_topic: impl Into<String>) -> Self {
+        self.response_topic = Some(response_topic.into());
+        self
+    }
+
+    pub fn correlation_data(mut self, correlation_data: impl Into<Bytes>) -> Self {
+        self.correlation_data = Some(correlation_data.into());
+        self
+    }
+
+    pub fn user_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.user_properties.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl From<PublishProperties> for MessageProperties {
+    fn from(value: PublishProperties) -> Self {
+        Self {
+            content_type: value.content_type,
+            response_topic: value.response_topic,
+            correlation_data: value.correlation_data,
+            user_properties: value.user_properties,
+        }
+    }
+}
+
+fn publish_properties(options: &PublishOptions) -> PublishProperties {
+    PublishProperties {
+        // rounded up, as an expiry of zero seconds would drop the message right away
+        message_expiry_interval: options.message_expiry.map(|expiry| {
+            let secs = expiry.as_secs() + u64::from(expiry.subsec_nanos() > 0);
+            u32::try_from(secs).unwrap_or(u32::MAX)
+        }),
+        content_type: options.properties.content_type.clone(),
+        response_topic: options.properties.response_topic.clone(),
+        correlation_data: options.properties.correlation_data.clone(),
+        user_properties: options.properties.user_properties.clone(),
+        ..Default::default()
+    }
+}
+
+fn qos_v5(qos: QoS) -> v5::mqttbytes::QoS {
+    match qos {
+        QoS::AtMostOnce => v5::mqttbytes::QoS::AtMostOnce,
+        QoS::AtLeastOnce => v5::mqttbytes::QoS::AtLeastOnce,
+        QoS::ExactlyOnce => v5::mqttbytes::QoS::ExactlyOnce,
+    }
+}
+
+fn qos_v3(qos: v5::mqttbytes::QoS) -> QoS {
+    match qos {
+        v5::mqttbytes::QoS::AtMostOnce => QoS::AtMostOnce,
+        v5::mqttbytes::QoS::AtLeastOnce => QoS::AtLeastOnce,
+        v5::mqttbytes::QoS::ExactlyOnce => QoS::ExactlyOnce,
+    }
+}
+
+/// An MQTT client, using either protocol version.
+#[derive(Clone, Debug)]
+pub enum MqttClient {
+    V3(rumqttc::AsyncClient),
+    V5(v5::AsyncClient),
+}
+
+impl MqttClient {
+    /// The MQTT v3.1.1 client, if connecting with that version.
+    pub fn as_v3(&self) -> Option<&rumqttc::AsyncClient> {
+        match self {
+            Self::V3(client) => Some(client),
+            Self::V5(_) => None,
+        }
+    }
+
+    /// The MQTT v5 client, if connecting with that version.
+    pub fn as_v5(&self) -> Option<&v5::AsyncClient> {
+        match self {
+            Self::V3(_) => None,
+            Self::V5(client) => Some(client),
+        }
+    }
+
+    /// Publish a message, the properties of the options are only used with MQTT v5.
+    pub async fn publish(
+        &self,
+        topic: impl Into<String>,
+        payload: impl Into<Vec<u8>>,
+        options: &PublishOptions,
+    ) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => {
+                client
+                    .publish(topic, options.qos, options.retain, payload)
+                    .await?
+            }
+            Self::V5(client) => {
+                client
+                    .publish_with_properties(
+                        topic,
+                        qos_v5(options.qos),
+                        options.retain,
+                        payload.into(),
+                        publish_properties(options),
+                    )
+                    .await?
+            }
+        }
+        Ok(())
+    }
+
+    /// Publish a message, without waiting for the request queue.
+    pub fn try_publish(
+        &self,
+        topic: impl Into<String>,
+        payload: impl Into<Vec<u8>>,
+        options: &PublishOptions,
+    ) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => client.try_publish(topic, options.qos, options.retain, payload)?,
+            Self::V5(client) => client.try_publish_with_properties(
+                topic,
+                qos_v5(options.qos),
+                options.retain,
+                payload.into(),
+                publish_properties(options),
+            )?,
+        }
+        Ok(())
+    }
+
+    pub async fn subscribe(
+        &self,
+        topic: impl Into<String>,
+        qos: QoS,
+    ) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => client.subscribe(topic, qos).await?,
+            Self::V5(client) => client.subscribe(topic, qos_v5(qos)).await?,
+        }
+        Ok(())
+    }
+
+    pub fn try_subscribe(&self, topic: impl Into<String>, qos: QoS) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => client.try_subscribe(topic, qos)?,
+            Self::V5(client) => client.try_subscribe(topic, qos_v5(qos))?,
+        }
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&self, topic: impl Into<String>) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => client.unsubscribe(topic).await?,
+            Self::V5(client) => client.unsubscribe(topic).await?,
+        }
+        Ok(())
+    }
+
+    pub fn try_disconnect(&self) -> Result<(), MqttClientError> {
+        match self {
+            Self::V3(client) => client.try_disconnect()?,
+            Self::V5(client) => client.try_disconnect()?,
+        }
+        Ok(())
+    }
+}
+
+/// A received message.
+#[derive(Clone, Debug)]
+pub(crate) struct Message {
+    pub(crate) topic: String,
+    pub(crate) payload: Bytes,
+    pub(crate) qos: QoS,
+    pub(crate) retain: bool,
+    /// The properties, if received using MQTT v5.
+    pub(crate) properties: Option<MessageProperties>,
+}
+
+/// The reason why the broker rejected a topic filter of a subscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubscribeFailure {
+    /// MQTT v3.1.1 doesn't report a reason.
+    V3,
+    V5(v5::mqttbytes::v5::SubscribeReasonCode),
+}
+
+impl std::fmt::Display for SubscribeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::V3 => f.write_str("Failure"),
+            Self::V5(code) => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// The events of the event loop, which are relevant to the connector.
+#[derive(Debug)]
+pub(crate) enum MqttEvent {
+    Connected,
+    Disconnected,
+    Message(Message),
+    /// A subscription was rejected, with the reasons of the failed topic filters.
+    SubscribeFailed(Vec<SubscribeFailure>),
+    /// The disconnect request was sent.
+    DisconnectSent,
+    Other,
+}
+
+impl From<Event> for MqttEvent {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Incoming(Incoming::ConnAck(_)) => Self::Connected,
+            Event::Incoming(Incoming::Disconnect) => Self::Disconnected,
+            Event::Incoming(Incoming::Publish(publish)) => Self::Message(Message {
+                topic: publish.topic,
+                payload: publish.payload,
+                qos: publish.qos,
+                retain: publish.retain,
+                properties: None,
+            }),
+            Event::Incoming(Incoming::SubAck(ack)) => Self::subscribe_failed(
+                ack.return_codes
+                    .iter()
+                    .filter(|code| matches!(code, SubscribeReasonCode::Failure))
+                    .map(|_| SubscribeFailure::V3),
+            ),
+            Event::Outgoing(Outgoing::Disconnect) => Self::DisconnectSent,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl From<v5::Event> for MqttEvent {
+    fn from(event: v5::Event) -> Self {
+        use v5::mqttbytes::v5::SubscribeReasonCode;
+
+        match event {
+            v5::Event::Incoming(Packet::ConnAck(_)) => Self::Connected,
+            v5::Event::Incoming(Packet::Disconnect(_)) => Self::Disconnected,
+            v5::Event::Incoming(Packet::Publish(publish)) => Self::Message(Message {
+                topic: String::from_utf8_lossy(&publish.topic).into_owned(),
+                payload: publish.payload,
+                qos: qos_v3(publish.qos),
+                retain: publish.retain,
+                properties: Some(publish.properties.map(Into::into).unwrap_or_default()),
+            }),
+            v5::Event::Incoming(Packet::SubAck(ack)) => Self::subscribe_failed(
+                ack.return_codes
+                    .iter()
+                    .filter(|code| !matches!(code, SubscribeReasonCode::Success(_)))
+                    .map(|code| SubscribeFailure::V5(*code)),
+            ),
+            v5::Event::Outgoing(Outgoing::Disconnect) => Self::DisconnectSent,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl MqttEvent {
+    fn subscribe_failed(reasons: impl Iterator<Item = SubscribeFailure>) -> Self {
+        let reasons = reasons.collect::<Vec<_>>();
+        if reasons.is_empty() {
+            Self::Other
+        } else {
+            Self::SubscribeFailed(reasons)
+        }
+    }
+}
+
+/// The event loop of the MQTT client, using either protocol version.
+pub(crate) enum MqttEventLoop {
+    V3(Box<rumqttc::EventLoop>),
+    V5(Box<v5::EventLoop>),
+}
+
+impl MqttEventLoop {
+    pub(crate) async fn poll(&mut self) -> Result<MqttEvent, MqttConnectionError> {
+        Ok(match self {
+            Self::V3(eventloop) => eventloop.poll().await?.into(),
+            Self::V5(eventloop) => eventloop.poll().await?.into(),
+        })
+    }
+
+    /// The number of outgoing messages, which are not yet acknowledged.
+    pub(crate) fn inflight(&self) -> u16 {
+        match self {
+            Self::V3(eventloop) => eventloop.state.inflight(),
+            Self::V5(eventloop) => eventloop.state.inflight(),
+        }
+    }
+
+    /// Drop the connection, the next poll reconnects and sends the pending requests again.
+    pub(crate) fn reconnect(&mut self) {
+        match self {
+            Self::V3(eventloop) => eventloop.clean(),
+            Self::V5(eventloop) => eventloop.clean(),
+        }
+    }
+}
+
+/// Options for creating the MQTT client.
+#[derive(Debug)]
+pub(crate) struct MqttConnectOptions {
+    pub(crate) version: MqttVersion,
+    pub(crate) client_id: String,
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) keep_alive: Duration,
+    pub(crate) tls: bool,
+    pub(crate) credentials: Option<(String, String)>,
+    /// The topic for publishing "offline" as last will.
+    pub(crate) last_will: Option<String>,
+}
+
+impl MqttConnectOptions {
+    pub(crate) fn create(self, cap: usize) -> (MqttClient, MqttEventLoop) {
+        let transport = if self.tls {
+            Transport::Tls(TlsConfiguration::Native)
+        } else {
+            Transport::Tcp
+        };
+
+        match self.version {
+            MqttVersion::V3 => {
+                let mut options = rumqttc::MqttOptions::new(self.client_id, self.host, self.port);
+                options.set_keep_alive(self.keep_alive);
+                options.set_transport(transport);
+                if let Some((username, password)) = self.credentials {
+                    options.set_credentials(username, password);
+                }
+                if let Some(topic) = self.last_will {
+                    options.set_last_will(rumqttc::LastWill::new(
+                        topic,
+                        "offline",
+                        QoS::AtLeastOnce,
+                        true,
+                    ));
+                }
+
+                log::debug!("Options: {options:#?}");
+
+                let (client, eventloop) = rumqttc::AsyncClient::new(options, cap);
+                (
+                    MqttClient::V3(client),
+                    MqttEventLoop::V3(Box::new(eventloop)),
+                )
+            }
+            MqttVersion::V5 => {
+                let mut options = v5::MqttOptions::new(self.client_id, self.host, self.port);
+                options.set_keep_alive(self.keep_alive);
+                options.set_transport(transport);
+                if let Some((username, password)) = self.credentials {
+                    options.set_credentials(username, password);
+                }
+                if let Some(topic) = self.last_will {
+                    options.set_last_will(v5::mqttbytes::v5::LastWill::new(
+                        topic,
+                        "offline",
+                        v5::mqttbytes::QoS::AtLeastOnce,
+                        true,
+                        None,
+                    ));
+                }
+
+                log::debug!("Options: {options:#?}");
+
+                let (client, eventloop) = v5::AsyncClient::new(options, cap);
+                (
+                    MqttClient::V5(client),
+                    MqttEventLoop::V5(Box::new(eventloop)),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_suback_v3() {
+        use rumqttc::SubAck;
+
+        let event = Event::Incoming(Incoming::SubAck(SubAck::new(
+            1,
+            vec![
+                SubscribeReasonCode::Success(QoS::AtLeastOnce),
+                SubscribeReasonCode::Failure,
+            ],
+        )));
+        assert!(matches!(
+            MqttEvent::from(event),
+            MqttEvent::SubscribeFailed(reasons) if reasons == [SubscribeFailure::V3]
+        ));
+
+        let event = Event::Incoming(Incoming::SubAck(SubAck::new(
+            1,
+            vec![SubscribeReasonCode::Success(QoS::AtMostOnce)],
+        )));
+        assert!(matches!(MqttEvent::from(event), MqttEvent::Other));
+    }
+
+    #[test]
+    fn test_suback_v5() {
+        use v5::mqttbytes::{
+            v5::{SubAck, SubscribeReasonCode},
+            QoS,
+        };
+
+        let event = v5::Event::Incoming(Packet::SubAck(SubAck {
+            pkid: 1,
+            return_codes: vec![
+                SubscribeReasonCode::NotAuthorized,
+                SubscribeReasonCode::Success(QoS::AtLeastOnce),
+                SubscribeReasonCode::QuotaExceeded,
+            ],
+            properties: None,
+        }));
+        assert!(matches!(
+            MqttEvent::from(event),
+            MqttEvent::SubscribeFailed(reasons) if reasons == [
+                SubscribeFailure::V5(SubscribeReasonCode::NotAuthorized),
+                SubscribeFailure::V5(SubscribeReasonCode::QuotaExceeded),
+            ]
+        ));
+
+        let event = v5::Event::Incoming(Packet::SubAck(SubAck {
+            pkid: 1,
+            return_codes: vec![SubscribeReasonCode::Success(QoS::AtLeastOnce)],
+            properties: None,
+        }));
+        assert!(matches!(MqttEvent::from(event), MqttEvent::Other));
+    }
+
+    #[test]
+    fn test_refused() {
+        assert!(
+            MqttConnectionError::from(rumqttc::ConnectionError::ConnectionRefused(
+                ConnectReturnCode::NotAuthorized
+            ))
+            .is_refused()
+        );
+        assert!(
+            !MqttConnectionError::from(rumqttc::ConnectionError::ConnectionRefused(
+                ConnectReturnCode::ServiceUnavailable
+            ))
+            .is_refused()
+        );
+        assert!(
+            MqttConnectionError::from(v5::ConnectionError::ConnectionRefused(
+                v5::mqttbytes::v5::ConnectReturnCode::Banned
+            ))
+            .is_refused()
+        );
+        assert!(!MqttConnectionError::from(rumqttc::ConnectionError::NetworkTimeout).is_refused());
+    }
+
+    #[test]
+    fn test_publish_properties() {
+        let options = PublishOptions::new()
+            .message_expiry(Duration::from_secs(60))
+            .properties(MessageProperties::new().user_property("trace-id", "1234"));
+
+        let properties = publish_properties(&options);
+        assert_eq!(properties.message_expiry_interval, Some(60));
+        assert_eq!(
+            properties.user_properties,
+            vec![("trace-id".to_string(), "1234".to_string())]
+        );
+
+        let expiry = |expiry| {
+            publish_properties(&PublishOptions::new().message_expiry(expiry))
+                .message_expiry_interval
+        };
+        assert_eq!(expiry(Duration::from_millis(1)), Some(1));
+        assert_eq!(expiry(Duration::from_millis(1500)), Some(2));
+        assert_eq!(expiry(Duration::from_secs(u64::MAX)), Some(u32::MAX));
+    }
+}
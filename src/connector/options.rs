@@ -1,4 +1,4 @@
-use crate::utils::is_default;
+use crate::{connector::MqttVersion, utils::is_default};
 use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -16,6 +16,11 @@ pub struct ConnectorOptions {
     #[cfg_attr(feature = "clap", arg(long, env))]
     pub topic_base: Option<String>,
 
+    /// The version of the MQTT protocol
+    #[serde(default, skip_serializing_if = "is_default")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_enum, default_value = "v3"))]
+    pub mqtt_version: MqttVersion,
+
     /// The MQTT's servers/brokers hostname
     /// #[cfg_attr(feature = "clap", arg(long, env))]
     #[cfg_attr(feature = "clap", arg(long, env))]
@@ -31,12 +36,60 @@ pub struct ConnectorOptions {
     #[cfg_attr(feature = "clap", arg(long, env))]
     pub disable_tls: bool,
 
-    #[serde(default = "default_keep_alive", skip_serializing_if = "is_default")]
+    #[serde(default = "default_keep_alive")]
     #[serde(with = "humantime_serde")]
     #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "5s"))]
     #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
     pub keep_alive: Duration,
 
+    /// The maximum random delay, before replaying announcements and states after connecting or
+    /// Home Assistant restarting
+    #[serde(default = "default_replay_jitter")]
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "5s"))]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
+    pub replay_jitter: Duration,
+
+    /// The delay before the first reconnect attempt, doubled with every failed attempt
+    #[serde(default = "default_reconnect_min_delay")]
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "1s"))]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
+    pub reconnect_min_delay: Duration,
+
+    /// The maximum delay between reconnect attempts
+    #[serde(default = "default_reconnect_max_delay")]
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "1m"))]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
+    pub reconnect_max_delay: Duration,
+
+    /// The maximum random delay, added to the delay between reconnect attempts
+    #[serde(default = "default_reconnect_jitter")]
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "1s"))]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
+    pub reconnect_jitter: Duration,
+
+    /// The maximum number of failed connection attempts in a row, before giving up, defaults to
+    /// unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "clap", arg(long, env))]
+    pub reconnect_max_attempts: Option<u32>,
+
+    /// Stop reconnecting when the broker refuses the connection, e.g. due to invalid credentials
+    #[serde(default, skip_serializing_if = "is_default")]
+    #[cfg_attr(feature = "clap", arg(long, env))]
+    pub fail_on_refusal: bool,
+
+    /// The maximum time for shutting down, including the handler and waiting for outstanding
+    /// messages to be acknowledged
+    #[serde(default = "default_shutdown_timeout")]
+    #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "clap", arg(long, env, value_parser = DurationValueParser, default_value = "5s"))]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "humantime_duration"))]
+    pub shutdown_timeout: Duration,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "clap", arg(long, env))]
     pub username: Option<String>,
@@ -89,3 +142,50 @@ impl clap::builder::TypedValueParser for DurationValueParser {
 fn default_keep_alive() -> Duration {
     Duration::from_secs(5)
 }
+
+fn default_replay_jitter() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_reconnect_min_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_reconnect_max_delay() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_reconnect_jitter() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_shutdown_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let options: ConnectorOptions =
+            serde_json::from_value(serde_json::json!({"host": "localhost"})).unwrap();
+        assert_eq!(options.replay_jitter, Duration::from_secs(5));
+
+        let options = ConnectorOptions {
+            keep_alive: Duration::ZERO,
+            replay_jitter: Duration::ZERO,
+            reconnect_min_delay: Duration::ZERO,
+            reconnect_jitter: Duration::ZERO,
+            shutdown_timeout: Duration::ZERO,
+            ..options
+        };
+        let value = serde_json::to_value(&options).unwrap();
+        assert_eq!(value["replayJitter"], "0s");
+        assert_eq!(
+            serde_json::from_value::<ConnectorOptions>(value).unwrap(),
+            options
+        );
+    }
+}
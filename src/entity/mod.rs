@@ -0,0 +1,86 @@
+//! High level entities, managed by an [`EntityRegistry`].
+
+mod registry;
+
+pub use registry::*;
+
+use crate::{
+    connector::{Client, ClientError},
+    model::{Command, DeviceId, Discovery, StateValue},
+};
+use std::{future::Future, sync::Arc};
+
+/// An entity, like a sensor or a switch.
+///
+/// Topics of the discovery information may start with `~`, which gets replaced with the base
+/// topic of the entity, `<registry base>/<object id>`. The registry announces the entity, and
+/// routes commands received on its command topics to [`Entity::command`].
+pub trait Entity: 'static {
+    /// The commands of the entity, [`std::convert::Infallible`] if it has none.
+    type Command: Command;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn id(&self) -> DeviceId;
+
+    fn discovery(&self) -> Discovery;
+
+    /// Handle a command received from Home Assistant.
+    fn command(
+        &mut self,
+        handle: &EntityHandle,
+        command: Self::Command,
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// A handle to a registered entity, providing its final discovery information.
+#[derive(Clone)]
+pub struct EntityHandle {
+    client: Client,
+    id: DeviceId,
+    discovery: Arc<Discovery>,
+}
+
+impl EntityHandle {
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn id(&self) -> &DeviceId {
+        &self.id
+    }
+
+    /// The discovery information, with absolute topics.
+    pub fn discovery(&self) -> &Discovery {
+        &self.discovery
+    }
+
+    /// Update the state, on the state topic of the entity.
+    pub async fn update_state(&self, payload: impl Into<Vec<u8>>) -> Result<(), ClientError> {
+        let topic = self
+            .discovery
+            .state_topic
+            .as_deref()
+            .ok_or(ClientError::MissingTopic("state_topic"))?;
+
+        self.client.update_state(topic, payload).await
+    }
+
+    /// Update the additional attributes, on the JSON attributes topic of the entity.
+    pub async fn update_attributes(
+        &self,
+        attributes: &impl serde::Serialize,
+    ) -> Result<(), ClientError> {
+        let topic = self
+            .discovery
+            .json_attributes_topic
+            .as_deref()
+            .ok_or(ClientError::MissingTopic("json_attributes_topic"))?;
+
+        self.client.update_attributes(topic, attributes).await
+    }
+
+    /// Publish a typed state, on the state topic of the entity.
+    pub async fn publish_state(&self, value: impl Into<StateValue>) -> Result<(), ClientError> {
+        self.client.publish_state(&self.discovery, value).await
+    }
+}
@@ -0,0 +1,364 @@
+use crate::{
+    connector::{Client, ClientError, ConnectorHandler, PublishOptions},
+    entity::{Entity, EntityHandle},
+    model::{Command, Discovery},
+};
+use bytes::Bytes;
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use rumqttc::QoS;
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::task::JoinHandle;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EntityError {
+    #[error("client error")]
+    Client(#[from] ClientError),
+    #[error("failed to handle command of {id}")]
+    Entity {
+        id: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// An [`Entity`], with its type erased.
+trait RegisteredEntity {
+    fn handle(&self) -> &EntityHandle;
+
+    /// Dispatch a message, returns `None` if the topic is not a command topic of the entity.
+    fn dispatch<'a>(
+        &'a mut self,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<LocalBoxFuture<'a, Result<(), EntityError>>>;
+}
+
+struct Registered<E: Entity> {
+    entity: E,
+    handle: EntityHandle,
+}
+
+impl<E: Entity> RegisteredEntity for Registered<E> {
+    fn handle(&self) -> &EntityHandle {
+        &self.handle
+    }
+
+    fn dispatch<'a>(
+        &'a mut self,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<LocalBoxFuture<'a, Result<(), EntityError>>> {
+        let command = match E::Command::decode(&self.handle.discovery, topic, payload)? {
+            Ok(command) => command,
+            Err(err) => {
+                // an invalid command must not fail the connection
+                log::warn!("Invalid command for {}: {err}", self.handle.id.id);
+                return Some(async { Ok(()) }.boxed_local());
+            }
+        };
+
+        Some(
+            async move {
+                self.entity
+                    .command(&self.handle, command)
+                    .await
+                    .map_err(|err| EntityError::Entity {
+                        id: self.handle.id.id.to_string(),
+                        source: Box::new(err),
+                    })
+            }
+            .boxed_local(),
+        )
+    }
+}
+
+/// A registry of entities, handling the connection for them.
+///
+/// The registry adds the announcements of all entities to the client, which publishes them when
+/// connected and when Home Assistant restarted. It subscribes to all command topics, and routes
+/// commands to the entity owning the topic. When shutting down, entities with their own
+/// availability topics are marked unavailable.
+///
+/// Subscribing runs in a background task, as the connector can only process the requests while
+/// the registry doesn't block it.
+pub struct EntityRegistry {
+    client: Client,
+    base: String,
+    entities: Vec<Box<dyn RegisteredEntity>>,
+    /// The pending subscriptions of the current connection.
+    setup: Option<JoinHandle<()>>,
+}
+
+impl EntityRegistry {
+    /// Create a new registry, deriving the topics of entities from `base`.
+    pub fn new(client: Client, base: impl Into<String>) -> Self {
+        Self {
+            client,
+            base: base.into(),
+            entities: Vec::new(),
+            setup: None,
+        }
+    }
+
+    /// Add an entity, returning a handle to it.
+    ///
+    /// Entities added after connecting will only be announced when reconnecting, or when Home
+    /// Assistant restarts.
+    pub fn add<E: Entity>(&mut self, entity: E) -> EntityHandle {
+        let id = entity.id();
+
+        let mut discovery = entity.discovery();
+        if discovery.base_topic.is_none() {
+            discovery.base_topic = Some(format!("{}/{}", self.base, id.id));
+        }
+        discovery.expand_topics();
+        let discovery = Discovery {
+            unique_id: discovery.unique_id.or_else(|| Some(id.unique_id())),
+            ..discovery
+        };
+
+        if let Err(err) = self.client.register(&id, &discovery) {
+            log::warn!("Failed to register {}: {err}", id.id);
+        }
+
+        let handle = EntityHandle {
+            client: self.client.clone(),
+            id,
+            discovery: Arc::new(discovery),
+        };
+
+        self.entities.push(Box::new(Registered {
+            entity,
+            handle: handle.clone(),
+        }));
+
+        handle
+    }
+
+    /// Subscribe to the command topics of all entities, in the background.
+    fn setup(&mut self) {
+        self.abort_setup();
+
+        let client = self.client.clone();
+        let handles = self
+            .entities
+            .iter()
+            .map(|entity| entity.handle().clone())
+            .collect::<Vec<_>>();
+
+        self.setup = Some(tokio::spawn(async move {
+            if let Err(err) = subscribe(&client, &handles).await {
+                log::warn!("Failed to subscribe to command topics: {err}");
+            }
+        }));
+    }
+
+    fn abort_setup(&mut self) {
+        if let Some(setup) = self.setup.take() {
+            setup.abort();
+        }
+    }
+
+    /// Mark all entities, which have their own availability topics, as unavailable.
+    async fn unavailable(&self) -> Result<(), ClientError> {
+        let mut topics = BTreeMap::new();
+        for entity in &self.entities {
+            for availability in &entity.handle().discovery.availability {
+                let payload = availability
+                    .payload_not_available
+                    .as_deref()
+                    .unwrap_or("offline");
+                topics.entry(availability.topic.as_str()).or_insert(payload);
+            }
+        }
+
+        for (topic, payload) in topics {
+            self.client
+                .mqtt
+                .publish(topic, payload, &PublishOptions::new().retain(true))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EntityRegistry {
+    fn drop(&mut self) {
+        self.abort_setup();
+    }
+}
+
+async fn subscribe(client: &Client, handles: &[EntityHandle]) -> Result<(), ClientError> {
+    for handle in handles {
+        for topic in handle.discovery.command_topics() {
+            client.subscribe(topic, QoS::AtLeastOnce).await?;
+        }
+    }
+    Ok(())
+}
+
+impl ConnectorHandler for EntityRegistry {
+    type Error = EntityError;
+
+    async fn connected(&mut self, state: bool) -> Result<(), Self::Error> {
+        if state {
+            self.setup();
+        } else {
+            self.abort_setup();
+        }
+        Ok(())
+    }
+
+    async fn restarted(&mut self) -> Result<(), Self::Error> {
+        // the connector replays the announcements
+        Ok(())
+    }
+
+    async fn message(&mut self, topic: String, payload: Bytes) -> Result<(), Self::Error> {
+        for entity in &mut self.entities {
+            if let Some(result) = entity.dispatch(&topic, &payload) {
+                return result.await;
+            }
+        }
+
+        log::debug!("No entity for message on: {topic}");
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), Self::Error> {
+        self.abort_setup();
+        Ok(self.unavailable().await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        connector::MqttClient,
+        model::{ButtonDiscovery, Component, DeviceId, SwitchCommand, SwitchDiscovery},
+    };
+    use rumqttc::{AsyncClient, MqttOptions};
+    use std::{cell::RefCell, convert::Infallible, rc::Rc, time::Duration};
+
+    struct Switch(Rc<RefCell<Vec<SwitchCommand>>>);
+
+    impl Entity for Switch {
+        type Command = SwitchCommand;
+        type Error = Infallible;
+
+        fn id(&self) -> DeviceId {
+            DeviceId::new("switch-1", Component::Switch)
+        }
+
+        fn discovery(&self) -> Discovery {
+            SwitchDiscovery::new("~/set").state_topic("~/state").build()
+        }
+
+        async fn command(
+            &mut self,
+            _handle: &EntityHandle,
+            command: Self::Command,
+        ) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(command);
+            Ok(())
+        }
+    }
+
+    struct Button(usize);
+
+    impl Entity for Button {
+        type Command = Infallible;
+        type Error = Infallible;
+
+        fn id(&self) -> DeviceId {
+            DeviceId::new(format!("button-{}", self.0), Component::Button)
+        }
+
+        fn discovery(&self) -> Discovery {
+            ButtonDiscovery::new("~/press").build()
+        }
+
+        async fn command(
+            &mut self,
+            _handle: &EntityHandle,
+            command: Self::Command,
+        ) -> Result<(), Self::Error> {
+            match command {}
+        }
+    }
+
+    fn registry() -> (EntityRegistry, rumqttc::EventLoop) {
+        let (mqtt, eventloop) = AsyncClient::new(MqttOptions::new("test", "localhost", 1883), 8);
+        (
+            EntityRegistry::new(
+                Client::new(MqttClient::V3(mqtt), "homeassistant".into(), None),
+                "agent",
+            ),
+            eventloop,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let (mut registry, _eventloop) = registry();
+
+        let commands = Rc::new(RefCell::new(Vec::new()));
+        let handle = registry.add(Switch(commands.clone()));
+
+        assert_eq!(
+            handle.discovery().unique_id.as_deref(),
+            Some("switch_switch-1")
+        );
+        assert_eq!(
+            handle.discovery().command_topic.as_deref(),
+            Some("agent/switch-1/set")
+        );
+        assert_eq!(
+            handle.discovery().state_topic.as_deref(),
+            Some("agent/switch-1/state")
+        );
+        // announced by the replay of the connector
+        let pending = registry.client.cache.lock().unwrap().pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "homeassistant/switch/switch-1/config");
+
+        registry
+            .message("agent/switch-1/set".into(), Bytes::from_static(b"ON"))
+            .await
+            .unwrap();
+        // invalid commands and unknown topics are ignored
+        registry
+            .message("agent/switch-1/set".into(), Bytes::from_static(b"TOGGLE"))
+            .await
+            .unwrap();
+        registry
+            .message("agent/switch-2/set".into(), Bytes::from_static(b"OFF"))
+            .await
+            .unwrap();
+
+        assert_eq!(*commands.borrow(), vec![SwitchCommand::TurnOn]);
+    }
+
+    #[tokio::test]
+    async fn test_connected_without_polling() {
+        // more requests than the channel can hold, without anyone polling the event loop
+        let (mut registry, _eventloop) = registry();
+        for i in 0..100 {
+            registry.add(Button(i));
+        }
+
+        tokio::time::timeout(Duration::from_secs(1), registry.connected(true))
+            .await
+            .expect("must not block the connector")
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert!(registry
+            .setup
+            .as_ref()
+            .is_some_and(|setup| !setup.is_finished()));
+
+        registry.connected(false).await.unwrap();
+        assert!(registry.setup.is_none());
+    }
+}
@@ -2,5 +2,6 @@
 #![warn(clippy::expect_used)]
 
 pub mod connector;
+pub mod entity;
 pub mod model;
 mod utils;
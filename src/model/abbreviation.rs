@@ -0,0 +1,559 @@
+use crate::model::{DeviceDiscovery, Discovery};
+use serde_json::{Map, Value};
+
+// also see: https://www.home-assistant.io/integrations/mqtt/#supported-abbreviations-in-mqtt-discovery-messages
+
+/// The format of the keys of a serialized discovery message.
+///
+/// Home Assistant accepts both forms. Deserializing always accepts both forms too.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// The full key names, like `state_topic`.
+    #[default]
+    Full,
+    /// The abbreviated key names, like `stat_t`, reducing the size of the payload.
+    Abbreviated,
+}
+
+/// Abbreviations of the fields of [`Discovery`], as `(full, abbreviated)`.
+pub(crate) const DISCOVERY: &[(&str, &str)] = &[
+    ("unique_id", "uniq_id"),
+    ("device", "dev"),
+    ("origin", "o"),
+    ("device_class", "dev_cla"),
+    ("state_class", "stat_cla"),
+    ("command_topic", "cmd_t"),
+    ("command_template", "cmd_tpl"),
+    ("state_topic", "stat_t"),
+    ("unit_of_measurement", "unit_of_meas"),
+    ("suggested_display_precision", "sug_dsp_prc"),
+    ("value_template", "val_tpl"),
+    ("enabled_by_default", "en"),
+    ("availability_mode", "avty_mode"),
+    ("availability", "avty"),
+    ("json_attributes_topic", "json_attr_t"),
+    ("json_attributes_template", "json_attr_tpl"),
+    // light
+    ("brightness_scale", "bri_scl"),
+    ("supported_color_modes", "sup_clrm"),
+    ("color_temp_kelvin", "clr_temp_k"),
+    ("min_kelvin", "min_k"),
+    ("max_kelvin", "max_k"),
+    ("min_mireds", "min_mirs"),
+    ("max_mireds", "max_mirs"),
+    ("effect_list", "fx_list"),
+    ("flash_time_short", "flsh_tsht"),
+    ("flash_time_long", "flsh_tlng"),
+    // climate
+    ("mode_command_topic", "mode_cmd_t"),
+    ("mode_state_topic", "mode_stat_t"),
+    ("temperature_command_topic", "temp_cmd_t"),
+    ("temperature_state_topic", "temp_stat_t"),
+    ("temperature_low_command_topic", "temp_lo_cmd_t"),
+    ("temperature_low_state_topic", "temp_lo_stat_t"),
+    ("temperature_high_command_topic", "temp_hi_cmd_t"),
+    ("temperature_high_state_topic", "temp_hi_stat_t"),
+    ("current_temperature_topic", "curr_temp_t"),
+    ("temperature_unit", "temp_unit"),
+    ("action_topic", "act_t"),
+    ("preset_mode_command_topic", "pr_mode_cmd_t"),
+    ("preset_mode_state_topic", "pr_mode_stat_t"),
+    ("preset_modes", "pr_modes"),
+    ("fan_mode_command_topic", "fan_mode_cmd_t"),
+    ("fan_mode_state_topic", "fan_mode_stat_t"),
+    // cover
+    ("payload_open", "pl_open"),
+    ("payload_close", "pl_cls"),
+    ("payload_stop", "pl_stop"),
+    ("state_open", "stat_open"),
+    ("state_opening", "stat_opening"),
+    ("state_closed", "stat_clsd"),
+    ("state_closing", "stat_closing"),
+    ("state_stopped", "stat_stopped"),
+    ("position_topic", "pos_t"),
+    ("set_position_topic", "set_pos_t"),
+    ("position_open", "pos_open"),
+    ("position_closed", "pos_clsd"),
+    ("tilt_command_topic", "tilt_cmd_t"),
+    ("tilt_status_topic", "tilt_status_t"),
+    ("tilt_opened_value", "tilt_opnd_val"),
+    ("tilt_closed_value", "tilt_clsd_val"),
+    // number, select, text
+    ("options", "ops"),
+    ("pattern", "ptrn"),
+    // button
+    ("payload_press", "pl_prs"),
+    // lock
+    ("payload_lock", "pl_lock"),
+    ("payload_unlock", "pl_unlk"),
+    ("state_locked", "stat_locked"),
+    ("state_locking", "stat_locking"),
+    ("state_unlocked", "stat_unlocked"),
+    ("state_unlocking", "stat_unlocking"),
+    ("state_jammed", "stat_jam"),
+    // valve
+    ("reports_position", "pos"),
+    // fan
+    ("payload_on", "pl_on"),
+    ("payload_off", "pl_off"),
+    ("state_on", "stat_on"),
+    ("state_off", "stat_off"),
+    ("percentage_command_topic", "pct_cmd_t"),
+    ("percentage_state_topic", "pct_stat_t"),
+    ("speed_range_min", "spd_rng_min"),
+    ("speed_range_max", "spd_rng_max"),
+    ("oscillation_command_topic", "osc_cmd_t"),
+    ("oscillation_state_topic", "osc_stat_t"),
+    ("payload_oscillation_on", "pl_osc_on"),
+    ("payload_oscillation_off", "pl_osc_off"),
+    ("direction_command_topic", "dir_cmd_t"),
+    ("direction_state_topic", "dir_stat_t"),
+    // device trigger
+    ("automation_type", "atype"),
+    ("topic", "t"),
+    ("subtype", "stype"),
+    ("payload", "pl"),
+    // event
+    ("event_types", "evt_typ"),
+];
+
+/// Abbreviations of the fields of [`crate::model::Device`].
+pub(crate) const DEVICE: &[(&str, &str)] = &[
+    ("identifiers", "ids"),
+    ("connections", "cns"),
+    ("manufacturer", "mf"),
+    ("model", "mdl"),
+    ("model_id", "mdl_id"),
+    ("hw_version", "hw"),
+    ("sw_version", "sw"),
+    ("serial_number", "sn"),
+    ("suggested_area", "sa"),
+    ("configuration_url", "cu"),
+    ("support_url", "url"),
+];
+
+/// Abbreviations of the fields of [`crate::model::Origin`].
+pub(crate) const ORIGIN: &[(&str, &str)] = &[("sw_version", "sw"), ("support_url", "url")];
+
+/// Abbreviations of the fields of [`DeviceDiscovery`].
+pub(crate) const DEVICE_DISCOVERY: &[(&str, &str)] =
+    &[("device", "dev"), ("origin", "o"), ("components", "cmps")];
+
+/// Abbreviations of the fields of [`crate::model::DeviceComponent`], in addition to the ones of
+/// [`Discovery`].
+pub(crate) const DEVICE_COMPONENT: &[(&str, &str)] = &[("platform", "p")];
+
+/// Abbreviations of the fields of [`crate::model::Availability`].
+pub(crate) const AVAILABILITY: &[(&str, &str)] = &[
+    ("topic", "t"),
+    ("payload_available", "pl_avail"),
+    ("payload_not_available", "pl_not_avail"),
+    ("value_template", "val_tpl"),
+];
+
+fn abbreviate(map: Map<String, Value>, abbreviations: &[(&str, &str)]) -> Map<String, Value> {
+    map.into_iter()
+        .map(|(key, value)| {
+            let key = abbreviations
+                .iter()
+                .find(|(full, _)| *full == key)
+                .map(|(_, short)| short.to_string())
+                .unwrap_or(key);
+            (key, value)
+        })
+        .collect()
+}
+
+/// Abbreviate the keys of a nested object, or of all objects in a nested array.
+fn abbreviate_nested(
+    map: &mut Map<String, Value>,
+    key: &str,
+    abbreviate_value: impl Fn(Map<String, Value>) -> Map<String, Value>,
+) {
+    let value = match map.remove(key) {
+        Some(Value::Object(value)) => Value::Object(abbreviate_value(value)),
+        Some(Value::Array(values)) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| match value {
+                    Value::Object(value) => Value::Object(abbreviate_value(value)),
+                    value => value,
+                })
+                .collect(),
+        ),
+        Some(value) => value,
+        None => return,
+    };
+    map.insert(key.into(), value);
+}
+
+fn abbreviate_discovery(mut map: Map<String, Value>) -> Map<String, Value> {
+    abbreviate_nested(&mut map, "device", |device| abbreviate(device, DEVICE));
+    abbreviate_nested(&mut map, "origin", |origin| abbreviate(origin, ORIGIN));
+    abbreviate_nested(&mut map, "availability", |availability| {
+        abbreviate(availability, AVAILABILITY)
+    });
+    abbreviate(map, DISCOVERY)
+}
+
+fn to_value(
+    value: impl serde::Serialize,
+    format: KeyFormat,
+    abbreviate: fn(Map<String, Value>) -> Map<String, Value>,
+) -> Result<Value, serde_json::Error> {
+    Ok(match serde_json::to_value(value)? {
+        Value::Object(map) => match format {
+            KeyFormat::Full => Value::Object(map),
+            KeyFormat::Abbreviated => Value::Object(abbreviate(map)),
+        },
+        value => value,
+    })
+}
+
+impl Discovery {
+    /// Serialize into a JSON value, using the requested key format.
+    pub fn to_value(&self, format: KeyFormat) -> Result<Value, serde_json::Error> {
+        to_value(self, format, abbreviate_discovery)
+    }
+}
+
+impl DeviceDiscovery {
+    /// Serialize into a JSON value, using the requested key format.
+    pub fn to_value(&self, format: KeyFormat) -> Result<Value, serde_json::Error> {
+        to_value(self, format, |mut map| {
+            abbreviate_nested(&mut map, "device", |device| abbreviate(device, DEVICE));
+            abbreviate_nested(&mut map, "origin", |origin| abbreviate(origin, ORIGIN));
+            if let Some(Value::Object(components)) = map.get_mut("components") {
+                for component in components.values_mut() {
+                    if let Value::Object(map) = component {
+                        *map =
+                            abbreviate(abbreviate_discovery(std::mem::take(map)), DEVICE_COMPONENT);
+                    }
+                }
+            }
+            abbreviate(map, DEVICE_DISCOVERY)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        Availability, AvailabilityMode, ColorMode, Component, Device, DeviceClass, DeviceComponent,
+        HvacMode, LightDiscovery, LightSchema, NumberMode, Origin, StateClass, TemperatureUnit,
+        TextPattern,
+    };
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_abbreviated() {
+        let discovery = Discovery {
+            unique_id: Some("light-1".into()),
+            device: Some(Device::new("device-1").manufacturer("ACME")),
+            origin: Some(Origin::new("agent").sw_version("1.0")),
+            availability: vec![Availability::new("device-1/status").payload_available("up")],
+            ..LightDiscovery::new("light-1/set")
+                .state_topic("light-1/state")
+                .brightness(100)
+                .build()
+        };
+
+        let value = discovery.to_value(KeyFormat::Abbreviated).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "name": null,
+                "uniq_id": "light-1",
+                "dev": {
+                    "ids": ["device-1"],
+                    "name": null,
+                    "mf": "ACME",
+                },
+                "o": {
+                    "name": "agent",
+                    "sw": "1.0",
+                },
+                "dev_cla": null,
+                "avty": [{
+                    "t": "device-1/status",
+                    "pl_avail": "up",
+                }],
+                "schema": "json",
+                "cmd_t": "light-1/set",
+                "stat_t": "light-1/state",
+                "brightness": true,
+                "bri_scl": 100,
+            })
+        );
+
+        // both forms deserialize into the same
+        assert_eq!(
+            serde_json::from_value::<Discovery>(value).unwrap(),
+            discovery
+        );
+        assert_eq!(
+            serde_json::from_value::<Discovery>(discovery.to_value(KeyFormat::Full).unwrap())
+                .unwrap(),
+            discovery
+        );
+    }
+
+    // The following use struct literals without defaults, so that new fields must be added here.
+
+    fn populated_device() -> Device {
+        Device {
+            identifiers: vec!["identifiers".into()],
+            connections: vec![("mac".into(), "02:5b:26:a8:dc:12".into())],
+            name: Some("name".into()),
+            base_topic: Some("~".into()),
+            manufacturer: Some("manufacturer".into()),
+            model: Some("model".into()),
+            model_id: Some("model_id".into()),
+            hw_version: Some("hw_version".into()),
+            sw_version: Some("sw_version".into()),
+            serial_number: Some("serial_number".into()),
+            suggested_area: Some("suggested_area".into()),
+            configuration_url: Some("configuration_url".into()),
+            via_device: Some("via_device".into()),
+            support_url: Some("support_url".into()),
+        }
+    }
+
+    fn populated_origin() -> Origin {
+        Origin {
+            name: "name".into(),
+            sw_version: Some("sw_version".into()),
+            support_url: Some("support_url".into()),
+        }
+    }
+
+    fn populated_availability() -> Availability {
+        Availability {
+            topic: "topic".into(),
+            payload_available: Some("payload_available".into()),
+            payload_not_available: Some("payload_not_available".into()),
+            value_template: Some("value_template".into()),
+        }
+    }
+
+    fn populated_discovery() -> Discovery {
+        Discovery {
+            name: Some("name".into()),
+            base_topic: Some("base_topic".into()),
+            unique_id: Some("unique_id".into()),
+            device: Some(populated_device()),
+            origin: Some(populated_origin()),
+            device_class: Some(DeviceClass::Other("temperature".into())),
+            state_class: Some(StateClass::Measurement),
+            command_topic: Some("command_topic".into()),
+            command_template: Some("command_template".into()),
+            state_topic: Some("state_topic".into()),
+            unit_of_measurement: Some("unit_of_measurement".into()),
+            suggested_display_precision: Some(1),
+            value_template: Some("value_template".into()),
+            enabled_by_default: Some(true),
+            availability_mode: AvailabilityMode::All,
+            availability: vec![populated_availability()],
+            json_attributes_topic: Some("json_attributes_topic".into()),
+            json_attributes_template: Some("json_attributes_template".into()),
+            schema: Some(LightSchema::Json),
+            brightness: Some(true),
+            brightness_scale: Some(1),
+            supported_color_modes: vec![ColorMode::Brightness],
+            color_temp_kelvin: Some(true),
+            min_kelvin: Some(1),
+            max_kelvin: Some(1),
+            min_mireds: Some(1),
+            max_mireds: Some(1),
+            effect: Some(true),
+            effect_list: vec!["effect_list".into()],
+            flash_time_short: Some(1),
+            flash_time_long: Some(1),
+            mode_command_topic: Some("mode_command_topic".into()),
+            mode_state_topic: Some("mode_state_topic".into()),
+            modes: vec![HvacMode::Heat],
+            temperature_command_topic: Some("temperature_command_topic".into()),
+            temperature_state_topic: Some("temperature_state_topic".into()),
+            temperature_low_command_topic: Some("temperature_low_command_topic".into()),
+            temperature_low_state_topic: Some("temperature_low_state_topic".into()),
+            temperature_high_command_topic: Some("temperature_high_command_topic".into()),
+            temperature_high_state_topic: Some("temperature_high_state_topic".into()),
+            current_temperature_topic: Some("current_temperature_topic".into()),
+            temperature_unit: Some(TemperatureUnit::Celsius),
+            min_temp: Some(1.5),
+            max_temp: Some(1.5),
+            temp_step: Some(1.5),
+            precision: Some(1.5),
+            action_topic: Some("action_topic".into()),
+            preset_mode_command_topic: Some("preset_mode_command_topic".into()),
+            preset_mode_state_topic: Some("preset_mode_state_topic".into()),
+            preset_modes: vec!["preset_modes".into()],
+            fan_mode_command_topic: Some("fan_mode_command_topic".into()),
+            fan_mode_state_topic: Some("fan_mode_state_topic".into()),
+            fan_modes: vec!["fan_modes".into()],
+            payload_open: Some("payload_open".into()),
+            payload_close: Some("payload_close".into()),
+            payload_stop: Some("payload_stop".into()),
+            state_open: Some("state_open".into()),
+            state_opening: Some("state_opening".into()),
+            state_closed: Some("state_closed".into()),
+            state_closing: Some("state_closing".into()),
+            state_stopped: Some("state_stopped".into()),
+            position_topic: Some("position_topic".into()),
+            set_position_topic: Some("set_position_topic".into()),
+            position_open: Some(1),
+            position_closed: Some(1),
+            tilt_command_topic: Some("tilt_command_topic".into()),
+            tilt_status_topic: Some("tilt_status_topic".into()),
+            tilt_min: Some(1),
+            tilt_max: Some(1),
+            tilt_opened_value: Some(1),
+            tilt_closed_value: Some(1),
+            min: Some(1.5),
+            max: Some(1.5),
+            step: Some(1.5),
+            mode: Some(NumberMode::Slider.into()),
+            options: vec!["options".into()],
+            pattern: Some(TextPattern::new("[a-z]+")),
+            payload_press: Some("payload_press".into()),
+            payload_lock: Some("payload_lock".into()),
+            payload_unlock: Some("payload_unlock".into()),
+            state_locked: Some("state_locked".into()),
+            state_locking: Some("state_locking".into()),
+            state_unlocked: Some("state_unlocked".into()),
+            state_unlocking: Some("state_unlocking".into()),
+            state_jammed: Some("state_jammed".into()),
+            reports_position: Some(true),
+            payload_on: Some("payload_on".into()),
+            payload_off: Some("payload_off".into()),
+            state_on: Some("state_on".into()),
+            state_off: Some("state_off".into()),
+            percentage_command_topic: Some("percentage_command_topic".into()),
+            percentage_state_topic: Some("percentage_state_topic".into()),
+            speed_range_min: Some(1),
+            speed_range_max: Some(1),
+            oscillation_command_topic: Some("oscillation_command_topic".into()),
+            oscillation_state_topic: Some("oscillation_state_topic".into()),
+            payload_oscillation_on: Some("payload_oscillation_on".into()),
+            payload_oscillation_off: Some("payload_oscillation_off".into()),
+            direction_command_topic: Some("direction_command_topic".into()),
+            direction_state_topic: Some("direction_state_topic".into()),
+            automation_type: Some("automation_type".into()),
+            topic: Some("topic".into()),
+            trigger_type: Some("trigger_type".into()),
+            subtype: Some("subtype".into()),
+            payload: Some("payload".into()),
+            event_types: vec!["event_types".into()],
+        }
+    }
+
+    /// The keys of an object, which are not abbreviations of the table.
+    fn unabbreviated<'a>(value: &'a Value, table: &[(&str, &str)]) -> BTreeSet<&'a str> {
+        value
+            .as_object()
+            .expect("must be an object")
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !table.iter().any(|(_, short)| short == key))
+            .collect()
+    }
+
+    /// Keys which don't have an abbreviation in Home Assistant.
+    const DISCOVERY_UNABBREVIATED: &[&str] = &[
+        "~",
+        "brightness",
+        "effect",
+        "fan_modes",
+        "max",
+        "max_temp",
+        "min",
+        "min_temp",
+        "mode",
+        "modes",
+        "name",
+        "precision",
+        "schema",
+        "step",
+        "temp_step",
+        "tilt_max",
+        "tilt_min",
+        "type",
+    ];
+
+    #[test]
+    fn test_populated_round_trip() {
+        let discovery = populated_discovery();
+        let value = discovery.to_value(KeyFormat::Abbreviated).unwrap();
+
+        // every field is either abbreviated, or known to have no abbreviation
+        assert_eq!(
+            unabbreviated(&value, DISCOVERY),
+            BTreeSet::from_iter(DISCOVERY_UNABBREVIATED.iter().copied())
+        );
+        assert_eq!(
+            unabbreviated(&value["dev"], DEVICE),
+            BTreeSet::from(["~", "name", "via_device"])
+        );
+        assert_eq!(unabbreviated(&value["o"], ORIGIN), BTreeSet::from(["name"]));
+        assert!(unabbreviated(&value["avty"][0], AVAILABILITY).is_empty());
+
+        // and every abbreviation is accepted when deserializing
+        assert_eq!(
+            serde_json::from_value::<Discovery>(value).unwrap(),
+            discovery
+        );
+    }
+
+    #[test]
+    fn test_populated_device_round_trip() {
+        let discovery = DeviceDiscovery {
+            device: populated_device(),
+            origin: populated_origin(),
+            components: [(
+                "sensor-1".to_string(),
+                DeviceComponent {
+                    platform: Component::Sensor,
+                    discovery: Discovery {
+                        device: None,
+                        ..populated_discovery()
+                    },
+                },
+            )]
+            .into(),
+        };
+        let value = discovery.to_value(KeyFormat::Abbreviated).unwrap();
+
+        assert!(unabbreviated(&value, DEVICE_DISCOVERY).is_empty());
+        let component = &value["cmps"]["sensor-1"];
+        assert_eq!(
+            unabbreviated(component, &[DISCOVERY, DEVICE_COMPONENT].concat()),
+            BTreeSet::from_iter(DISCOVERY_UNABBREVIATED.iter().copied())
+        );
+
+        assert_eq!(
+            serde_json::from_value::<DeviceDiscovery>(value).unwrap(),
+            discovery
+        );
+    }
+
+    #[test]
+    fn test_unique() {
+        for table in [
+            DISCOVERY,
+            DEVICE,
+            ORIGIN,
+            DEVICE_DISCOVERY,
+            DEVICE_COMPONENT,
+            AVAILABILITY,
+        ] {
+            for (i, (full, short)) in table.iter().enumerate() {
+                assert!(
+                    !table[i + 1..].iter().any(|(f, s)| f == full || s == short),
+                    "duplicate abbreviation: {full} / {short}"
+                );
+            }
+        }
+    }
+}
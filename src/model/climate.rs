@@ -0,0 +1,275 @@
+use crate::model::{is_topic, parse_number, parse_str, Command, CommandError, Discovery};
+use std::str::FromStr;
+
+// also see: https://www.home-assistant.io/integrations/climate.mqtt/
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::AsRefStr,
+    strum::EnumString,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HvacMode {
+    Auto,
+    Off,
+    Cool,
+    Heat,
+    Dry,
+    FanOnly,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TemperatureUnit {
+    #[serde(rename = "C")]
+    Celsius,
+    #[serde(rename = "F")]
+    Fahrenheit,
+}
+
+/// Builder for a climate device.
+///
+/// Each aspect of the device (mode, target temperature, presets, …) has its own command topic.
+/// Commands received on those topics can be decoded into a [`ClimateCommand`].
+#[derive(Clone, Debug, Default)]
+pub struct ClimateDiscovery {
+    discovery: Discovery,
+}
+
+impl ClimateDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable setting the mode, using the provided supported modes.
+    pub fn modes(
+        mut self,
+        command_topic: impl Into<String>,
+        modes: impl IntoIterator<Item = HvacMode>,
+    ) -> Self {
+        self.discovery.mode_command_topic = Some(command_topic.into());
+        self.discovery.modes = modes.into_iter().collect();
+        self
+    }
+
+    pub fn mode_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.mode_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable setting the target temperature.
+    pub fn target_temperature(mut self, command_topic: impl Into<String>) -> Self {
+        self.discovery.temperature_command_topic = Some(command_topic.into());
+        self
+    }
+
+    pub fn target_temperature_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.temperature_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable setting a target temperature range.
+    pub fn target_temperature_range(
+        mut self,
+        low_command_topic: impl Into<String>,
+        high_command_topic: impl Into<String>,
+    ) -> Self {
+        self.discovery.temperature_low_command_topic = Some(low_command_topic.into());
+        self.discovery.temperature_high_command_topic = Some(high_command_topic.into());
+        self
+    }
+
+    pub fn current_temperature_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.current_temperature_topic = Some(topic.into());
+        self
+    }
+
+    /// Set the limits of the target temperature.
+    pub fn temperature_limits(mut self, min: f64, max: f64, step: f64) -> Self {
+        self.discovery.min_temp = Some(min);
+        self.discovery.max_temp = Some(max);
+        self.discovery.temp_step = Some(step);
+        self
+    }
+
+    pub fn temperature_unit(mut self, unit: TemperatureUnit) -> Self {
+        self.discovery.temperature_unit = Some(unit);
+        self
+    }
+
+    pub fn precision(mut self, precision: f64) -> Self {
+        self.discovery.precision = Some(precision);
+        self
+    }
+
+    pub fn action_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.action_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable preset modes, using the provided list.
+    pub fn preset_modes<I>(mut self, command_topic: impl Into<String>, presets: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.discovery.preset_mode_command_topic = Some(command_topic.into());
+        self.discovery.preset_modes = presets.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn preset_mode_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.preset_mode_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable fan modes, using the provided list.
+    pub fn fan_modes<I>(mut self, command_topic: impl Into<String>, modes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.discovery.fan_mode_command_topic = Some(command_topic.into());
+        self.discovery.fan_modes = modes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn fan_mode_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.fan_mode_state_topic = Some(topic.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<ClimateDiscovery> for Discovery {
+    fn from(value: ClimateDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// A command for a climate device.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClimateCommand {
+    SetMode(HvacMode),
+    SetTargetTemperature(f64),
+    SetTargetTemperatureLow(f64),
+    SetTargetTemperatureHigh(f64),
+    SetPresetMode(String),
+    SetFanMode(String),
+}
+
+impl Command for ClimateCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        let command = if is_topic(&discovery.mode_command_topic, topic) {
+            // without announced modes, Home Assistant uses all of them
+            parse_str(payload).and_then(|mode| match HvacMode::from_str(mode) {
+                Ok(mode) if discovery.modes.is_empty() || discovery.modes.contains(&mode) => {
+                    Ok(Self::SetMode(mode))
+                }
+                Ok(mode) => Err(CommandError::UnknownOption(mode.as_ref().to_string())),
+                Err(_) => Err(CommandError::invalid_payload(payload)),
+            })
+        } else if is_topic(&discovery.temperature_command_topic, topic) {
+            parse_number(payload).map(Self::SetTargetTemperature)
+        } else if is_topic(&discovery.temperature_low_command_topic, topic) {
+            parse_number(payload).map(Self::SetTargetTemperatureLow)
+        } else if is_topic(&discovery.temperature_high_command_topic, topic) {
+            parse_number(payload).map(Self::SetTargetTemperatureHigh)
+        } else if is_topic(&discovery.preset_mode_command_topic, topic) {
+            parse_str(payload).and_then(|preset| {
+                if discovery.preset_modes.iter().any(|p| p == preset) {
+                    Ok(Self::SetPresetMode(preset.to_string()))
+                } else {
+                    Err(CommandError::UnknownOption(preset.to_string()))
+                }
+            })
+        } else if is_topic(&discovery.fan_mode_command_topic, topic) {
+            // without announced fan modes, Home Assistant uses its defaults
+            parse_str(payload).and_then(|mode| {
+                if discovery.fan_modes.is_empty() || discovery.fan_modes.iter().any(|m| m == mode) {
+                    Ok(Self::SetFanMode(mode.to_string()))
+                } else {
+                    Err(CommandError::UnknownOption(mode.to_string()))
+                }
+            })
+        } else {
+            return None;
+        };
+
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let discovery = ClimateDiscovery::new()
+            .modes("hvac/mode/set", [HvacMode::Off, HvacMode::Heat])
+            .target_temperature("hvac/temperature/set")
+            .preset_modes("hvac/preset/set", ["eco", "comfort"])
+            .build();
+
+        assert_eq!(
+            ClimateCommand::decode(&discovery, "hvac/mode/set", b"heat")
+                .unwrap()
+                .unwrap(),
+            ClimateCommand::SetMode(HvacMode::Heat)
+        );
+        assert!(matches!(
+            ClimateCommand::decode(&discovery, "hvac/mode/set", b"fan_only").unwrap(),
+            Err(CommandError::UnknownOption(mode)) if mode == "fan_only"
+        ));
+        assert!(matches!(
+            ClimateCommand::decode(&discovery, "hvac/mode/set", b"warm").unwrap(),
+            Err(CommandError::InvalidPayload(mode)) if mode == "warm"
+        ));
+        assert_eq!(
+            ClimateCommand::decode(&discovery, "hvac/temperature/set", b"21.5")
+                .unwrap()
+                .unwrap(),
+            ClimateCommand::SetTargetTemperature(21.5)
+        );
+        assert_eq!(
+            ClimateCommand::decode(&discovery, "hvac/preset/set", b"eco")
+                .unwrap()
+                .unwrap(),
+            ClimateCommand::SetPresetMode("eco".into())
+        );
+        assert!(matches!(
+            ClimateCommand::decode(&discovery, "hvac/preset/set", b"boost").unwrap(),
+            Err(CommandError::UnknownOption(preset)) if preset == "boost"
+        ));
+        assert!(
+            ClimateCommand::decode(&discovery, "hvac/temperature/set", b"warm")
+                .unwrap()
+                .is_err()
+        );
+        assert!(ClimateCommand::decode(&discovery, "hvac/other", b"").is_none());
+
+        let discovery = ClimateDiscovery::new()
+            .fan_modes("hvac/fan/set", ["low", "high"])
+            .build();
+        assert!(matches!(
+            ClimateCommand::decode(&discovery, "hvac/fan/set", b"medium").unwrap(),
+            Err(CommandError::UnknownOption(mode)) if mode == "medium"
+        ));
+    }
+}
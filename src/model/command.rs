@@ -0,0 +1,88 @@
+use crate::model::Discovery;
+
+/// An error decoding a command received from Home Assistant.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("invalid JSON payload: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid payload: {0:?}")]
+    InvalidPayload(String),
+    #[error("value {value} is out of range ({min} to {max})")]
+    OutOfRange { value: f64, min: f64, max: f64 },
+    #[error("value {value} is not a step of {step}, starting at {min}")]
+    InvalidStep { value: f64, min: f64, step: f64 },
+    #[error("unknown option: {0:?}")]
+    UnknownOption(String),
+    #[error("length {length} is out of range ({min} to {max})")]
+    InvalidLength {
+        length: usize,
+        min: usize,
+        max: usize,
+    },
+    #[error("value {value:?} doesn't match pattern {pattern:?}")]
+    PatternMismatch { value: String, pattern: String },
+    #[error("invalid pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+}
+
+impl CommandError {
+    pub(crate) fn invalid_payload(payload: &[u8]) -> Self {
+        Self::InvalidPayload(String::from_utf8_lossy(payload).into_owned())
+    }
+}
+
+/// A typed command, decoded from a message received on one of the command topics of an entity.
+pub trait Command: Sized {
+    /// Decode a command for the entity described by `discovery`.
+    ///
+    /// Returns `None` if `topic` is not a command topic of the entity.
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>>;
+}
+
+/// An entity without any commands, like a sensor.
+impl Command for std::convert::Infallible {
+    fn decode(
+        _discovery: &Discovery,
+        _topic: &str,
+        _payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        None
+    }
+}
+
+/// Check if `topic` is the (configured) topic `expected`.
+pub(crate) fn is_topic(expected: &Option<String>, topic: &str) -> bool {
+    expected.as_deref() == Some(topic)
+}
+
+/// Check if `payload` matches the configured payload, or its default.
+pub(crate) fn is_payload(payload: &[u8], configured: &Option<String>, default: &str) -> bool {
+    payload == configured.as_deref().unwrap_or(default).as_bytes()
+}
+
+/// Check if `payload` matches the configured payload, which has no default.
+pub(crate) fn is_configured_payload(payload: &[u8], configured: &Option<String>) -> bool {
+    configured.as_deref().map(str::as_bytes) == Some(payload)
+}
+
+pub(crate) fn parse_str(payload: &[u8]) -> Result<&str, CommandError> {
+    std::str::from_utf8(payload).map_err(|_| CommandError::invalid_payload(payload))
+}
+
+pub(crate) fn parse_number(payload: &[u8]) -> Result<f64, CommandError> {
+    parse_str(payload)?
+        .trim()
+        .parse()
+        .map_err(|_| CommandError::invalid_payload(payload))
+}
+
+pub(crate) fn parse_integer(payload: &[u8]) -> Result<i64, CommandError> {
+    parse_str(payload)?
+        .trim()
+        .parse()
+        .map_err(|_| CommandError::invalid_payload(payload))
+}
@@ -1,11 +1,24 @@
 use std::fmt::Formatter;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum Component {
     Button,
     Switch,
     BinarySensor,
     Sensor,
+    Light,
+    Climate,
+    Cover,
+    Number,
+    Select,
+    Text,
+    Lock,
+    Valve,
+    Fan,
+    DeviceAutomation,
+    Event,
 }
 
 impl AsRef<str> for Component {
@@ -13,8 +26,19 @@ impl AsRef<str> for Component {
         match self {
             Self::BinarySensor => "binary_sensor",
             Self::Button => "button",
+            Self::Climate => "climate",
+            Self::Cover => "cover",
+            Self::DeviceAutomation => "device_automation",
+            Self::Event => "event",
+            Self::Fan => "fan",
+            Self::Light => "light",
+            Self::Lock => "lock",
+            Self::Number => "number",
+            Self::Select => "select",
             Self::Sensor => "sensor",
             Self::Switch => "switch",
+            Self::Text => "text",
+            Self::Valve => "valve",
         }
     }
 }
@@ -0,0 +1,269 @@
+use crate::model::{
+    is_payload, is_topic, parse_integer, Command, CommandError, CoverClass, Discovery,
+};
+
+// also see: https://www.home-assistant.io/integrations/cover.mqtt/
+
+const DEFAULT_POSITION_CLOSED: i64 = 0;
+const DEFAULT_POSITION_OPEN: i64 = 100;
+const DEFAULT_TILT_MIN: i64 = 0;
+const DEFAULT_TILT_MAX: i64 = 100;
+
+/// Builder for a cover.
+///
+/// Commands received on the command, position and tilt topics can be decoded into a
+/// [`CoverCommand`].
+#[derive(Clone, Debug)]
+pub struct CoverDiscovery {
+    discovery: Discovery,
+}
+
+impl CoverDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: CoverClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    /// Set the topic the [`CoverState`] is published on.
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Override the default command payloads (`OPEN`, `CLOSE`, `STOP`).
+    pub fn payloads(
+        mut self,
+        open: impl Into<String>,
+        close: impl Into<String>,
+        stop: impl Into<String>,
+    ) -> Self {
+        self.discovery.payload_open = Some(open.into());
+        self.discovery.payload_close = Some(close.into());
+        self.discovery.payload_stop = Some(stop.into());
+        self
+    }
+
+    /// Enable reporting and setting the position.
+    pub fn position(
+        mut self,
+        position_topic: impl Into<String>,
+        set_position_topic: impl Into<String>,
+    ) -> Self {
+        self.discovery.position_topic = Some(position_topic.into());
+        self.discovery.set_position_topic = Some(set_position_topic.into());
+        self
+    }
+
+    /// Set the range of the position, defaults to `0` (closed) to `100` (open).
+    pub fn position_range(mut self, closed: i64, open: i64) -> Self {
+        self.discovery.position_closed = Some(closed);
+        self.discovery.position_open = Some(open);
+        self
+    }
+
+    /// Enable tilting.
+    pub fn tilt(
+        mut self,
+        command_topic: impl Into<String>,
+        status_topic: impl Into<String>,
+    ) -> Self {
+        self.discovery.tilt_command_topic = Some(command_topic.into());
+        self.discovery.tilt_status_topic = Some(status_topic.into());
+        self
+    }
+
+    /// Set the range of the tilt, defaults to `0` to `100`.
+    pub fn tilt_range(mut self, min: i64, max: i64) -> Self {
+        self.discovery.tilt_min = Some(min);
+        self.discovery.tilt_max = Some(max);
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<CoverDiscovery> for Discovery {
+    fn from(value: CoverDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// The state of a cover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverState {
+    Open,
+    Opening,
+    Closed,
+    Closing,
+    Stopped,
+}
+
+impl CoverState {
+    /// The payload for this state, as configured by the discovery information.
+    pub fn payload<'a>(&self, discovery: &'a Discovery) -> &'a str {
+        let (configured, default) = match self {
+            Self::Open => (&discovery.state_open, "open"),
+            Self::Opening => (&discovery.state_opening, "opening"),
+            Self::Closed => (&discovery.state_closed, "closed"),
+            Self::Closing => (&discovery.state_closing, "closing"),
+            Self::Stopped => (&discovery.state_stopped, "stopped"),
+        };
+        configured.as_deref().unwrap_or(default)
+    }
+}
+
+/// A command for a cover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverCommand {
+    Open,
+    Close,
+    Stop,
+    /// Set the position, in the range of `position_closed` to `position_open`.
+    SetPosition(i64),
+    /// Set the tilt, in the range of `tilt_min` to `tilt_max`.
+    SetTilt(i64),
+}
+
+impl Command for CoverCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        let command = if is_topic(&discovery.command_topic, topic) {
+            if is_payload(payload, &discovery.payload_open, "OPEN") {
+                Ok(Self::Open)
+            } else if is_payload(payload, &discovery.payload_close, "CLOSE") {
+                Ok(Self::Close)
+            } else if is_payload(payload, &discovery.payload_stop, "STOP") {
+                Ok(Self::Stop)
+            } else {
+                Err(CommandError::invalid_payload(payload))
+            }
+        } else if is_topic(&discovery.set_position_topic, topic) {
+            parse_integer(payload)
+                .and_then(|value| {
+                    Self::check_range(
+                        value,
+                        discovery.position_closed.unwrap_or(DEFAULT_POSITION_CLOSED),
+                        discovery.position_open.unwrap_or(DEFAULT_POSITION_OPEN),
+                    )
+                })
+                .map(Self::SetPosition)
+        } else if is_topic(&discovery.tilt_command_topic, topic) {
+            parse_integer(payload)
+                .and_then(|value| {
+                    Self::check_range(
+                        value,
+                        discovery.tilt_min.unwrap_or(DEFAULT_TILT_MIN),
+                        discovery.tilt_max.unwrap_or(DEFAULT_TILT_MAX),
+                    )
+                })
+                .map(Self::SetTilt)
+        } else {
+            return None;
+        };
+
+        Some(command)
+    }
+}
+
+impl CoverCommand {
+    /// Check if the value is within the range, which may also be inverted.
+    fn check_range(value: i64, start: i64, end: i64) -> Result<i64, CommandError> {
+        let (min, max) = (start.min(end), start.max(end));
+        if (min..=max).contains(&value) {
+            Ok(value)
+        } else {
+            Err(CommandError::OutOfRange {
+                value: value as f64,
+                min: min as f64,
+                max: max as f64,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let discovery = CoverDiscovery::new("cover/set")
+            .device_class(CoverClass::Garage)
+            .payloads("up", "down", "halt")
+            .position("cover/position", "cover/position/set")
+            .build();
+
+        assert_eq!(discovery.device_class, Some(CoverClass::Garage.into()));
+        assert_eq!(
+            CoverCommand::decode(&discovery, "cover/set", b"up")
+                .unwrap()
+                .unwrap(),
+            CoverCommand::Open
+        );
+        assert_eq!(
+            CoverCommand::decode(&discovery, "cover/set", b"halt")
+                .unwrap()
+                .unwrap(),
+            CoverCommand::Stop
+        );
+        assert!(CoverCommand::decode(&discovery, "cover/set", b"OPEN")
+            .unwrap()
+            .is_err());
+        assert_eq!(
+            CoverCommand::decode(&discovery, "cover/position/set", b"42")
+                .unwrap()
+                .unwrap(),
+            CoverCommand::SetPosition(42)
+        );
+        assert!(matches!(
+            CoverCommand::decode(&discovery, "cover/position/set", b"101"),
+            Some(Err(CommandError::OutOfRange { .. }))
+        ));
+        assert_eq!(CoverState::Closing.payload(&discovery), "closing");
+    }
+
+    #[test]
+    fn test_range() {
+        let discovery = CoverDiscovery::new("cover/set")
+            .position("cover/position", "cover/position/set")
+            .position_range(100, 0)
+            .tilt("cover/tilt/set", "cover/tilt")
+            .tilt_range(-90, 90)
+            .build();
+
+        assert_eq!(
+            CoverCommand::decode(&discovery, "cover/position/set", b"100")
+                .unwrap()
+                .unwrap(),
+            CoverCommand::SetPosition(100)
+        );
+        assert!(matches!(
+            CoverCommand::decode(&discovery, "cover/position/set", b"-1"),
+            Some(Err(CommandError::OutOfRange { .. }))
+        ));
+        assert_eq!(
+            CoverCommand::decode(&discovery, "cover/tilt/set", b"-45")
+                .unwrap()
+                .unwrap(),
+            CoverCommand::SetTilt(-45)
+        );
+        assert!(matches!(
+            CoverCommand::decode(&discovery, "cover/tilt/set", b"91"),
+            Some(Err(CommandError::OutOfRange { .. }))
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+// also see: https://www.home-assistant.io/integrations/sensor.mqtt/#device
+
+/// The device an entity belongs to, as shown in the device registry of Home Assistant.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Device {
+    #[serde(alias = "ids")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifiers: Vec<String>,
+
+    /// Connections of the device, as tuples of type and value, like `("mac", "02:5b:26:a8:dc:12")`.
+    #[serde(alias = "cns")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub connections: Vec<(String, String)>,
+
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The base topic of the device's entities.
+    ///
+    /// Home Assistant only evaluates `~` on the root of a discovery message, so this is only used
+    /// as a fallback when compressing or expanding the topics of a [`crate::model::Discovery`].
+    #[serde(rename = "~")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_topic: Option<String>,
+
+    #[serde(alias = "mf")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+
+    #[serde(alias = "mdl")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    #[serde(alias = "mdl_id")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+
+    #[serde(alias = "hw")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hw_version: Option<String>,
+
+    /// Software version of the application that supplies the discovered MQTT item.
+    #[serde(alias = "sw")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sw_version: Option<String>,
+
+    #[serde(alias = "sn")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+
+    /// The area the device should be placed in, when it is added to Home Assistant.
+    #[serde(alias = "sa")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_area: Option<String>,
+
+    /// A URL to configure the device.
+    #[serde(alias = "cu")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configuration_url: Option<String>,
+
+    /// The identifier of a device that routes messages between this device and Home Assistant,
+    /// like a hub.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via_device: Option<String>,
+
+    /// Support URL of the application that supplies the discovered MQTT item.
+    #[serde(alias = "url")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support_url: Option<String>,
+}
+
+impl Device {
+    pub fn new(identifier: impl Into<String>) -> Self {
+        Self {
+            identifiers: vec![identifier.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Add an additional identifier.
+    pub fn identifier(mut self, identifier: impl Into<String>) -> Self {
+        self.identifiers.push(identifier.into());
+        self
+    }
+
+    /// Add a connection, like `("mac", "02:5b:26:a8:dc:12")` or `("zigbee", "0x00158d0001a2b3c4")`.
+    pub fn connection(mut self, r#type: impl Into<String>, value: impl Into<String>) -> Self {
+        self.connections.push((r#type.into(), value.into()));
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn manufacturer(mut self, manufacturer: impl Into<String>) -> Self {
+        self.manufacturer = Some(manufacturer.into());
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn model_id(mut self, model_id: impl Into<String>) -> Self {
+        self.model_id = Some(model_id.into());
+        self
+    }
+
+    pub fn hw_version(mut self, hw_version: impl Into<String>) -> Self {
+        self.hw_version = Some(hw_version.into());
+        self
+    }
+
+    pub fn sw_version(mut self, sw_version: impl Into<String>) -> Self {
+        self.sw_version = Some(sw_version.into());
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: impl Into<String>) -> Self {
+        self.serial_number = Some(serial_number.into());
+        self
+    }
+
+    pub fn suggested_area(mut self, suggested_area: impl Into<String>) -> Self {
+        self.suggested_area = Some(suggested_area.into());
+        self
+    }
+
+    pub fn configuration_url(mut self, configuration_url: impl Into<String>) -> Self {
+        self.configuration_url = Some(configuration_url.into());
+        self
+    }
+
+    pub fn via_device(mut self, via_device: impl Into<String>) -> Self {
+        self.via_device = Some(via_device.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn device() -> Device {
+        Device::new("sensor-1")
+            .connection("mac", "02:5b:26:a8:dc:12")
+            .name("Sensor 1")
+            .manufacturer("ACME")
+            .model("Sensor")
+            .model_id("S-1")
+            .hw_version("1.0")
+            .sw_version("2.1.0")
+            .serial_number("1234")
+            .suggested_area("Kitchen")
+            .configuration_url("http://sensor-1.local")
+            .via_device("hub-1")
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::to_value(device()).unwrap(),
+            json!({
+                "identifiers": ["sensor-1"],
+                "connections": [["mac", "02:5b:26:a8:dc:12"]],
+                "name": "Sensor 1",
+                "manufacturer": "ACME",
+                "model": "Sensor",
+                "model_id": "S-1",
+                "hw_version": "1.0",
+                "sw_version": "2.1.0",
+                "serial_number": "1234",
+                "suggested_area": "Kitchen",
+                "configuration_url": "http://sensor-1.local",
+                "via_device": "hub-1",
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_abbreviated() {
+        let parsed: Device = serde_json::from_value(json!({
+            "ids": ["sensor-1"],
+            "cns": [["mac", "02:5b:26:a8:dc:12"]],
+            "name": "Sensor 1",
+            "mf": "ACME",
+            "mdl": "Sensor",
+            "mdl_id": "S-1",
+            "hw": "1.0",
+            "sw": "2.1.0",
+            "sn": "1234",
+            "sa": "Kitchen",
+            "cu": "http://sensor-1.local",
+            "via_device": "hub-1",
+        }))
+        .unwrap();
+
+        assert_eq!(parsed, device());
+    }
+}
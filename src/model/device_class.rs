@@ -1,65 +1,138 @@
-use std::fmt::Debug;
+use crate::model::Component;
+use std::{fmt::Debug, str::FromStr};
 
-// for values see: https://github.com/home-assistant/core/blob/dev/homeassistant/components/number/const.py
+// for values see:
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/binary_sensor/__init__.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/button/__init__.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/cover/__init__.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/event/__init__.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/sensor/const.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/switch/__init__.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/valve/__init__.py
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum ButtonClass {
     Identify,
     Restart,
     Update,
 }
 
-impl AsRef<str> for ButtonClass {
-    fn as_ref(&self) -> &str {
-        match self {
-            Self::Identify => "identify",
-            Self::Restart => "restart",
-            Self::Update => "update",
-        }
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum BinarySensorClass {
+    Battery,
+    BatteryCharging,
+    #[strum(to_string = "carbon_monoxide")]
+    #[serde(rename = "carbon_monoxide")]
+    Co,
+    Cold,
+    Connectivity,
+    Door,
+    GarageDoor,
+    Gas,
+    Heat,
+    Light,
+    Lock,
+    Moisture,
     Motion,
+    Moving,
+    Occupancy,
+    Opening,
+    Plug,
+    Power,
+    Presence,
+    Problem,
+    Running,
+    Safety,
+    Smoke,
+    Sound,
+    Tamper,
+    Update,
+    Vibration,
+    Window,
 }
 
-impl AsRef<str> for BinarySensorClass {
-    fn as_ref(&self) -> &str {
-        match self {
-            Self::Motion => "motion",
-        }
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum SwitchClass {
     Outlet,
     Switch,
 }
 
-impl AsRef<str> for SwitchClass {
-    fn as_ref(&self) -> &str {
-        match self {
-            Self::Outlet => "outlet",
-            Self::Switch => "switch",
-        }
-    }
-}
-
 #[derive(
-    Copy, Clone, Eq, PartialEq, Debug, strum::AsRefStr, strum::EnumString, strum::VariantNames,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum SensorClass {
+    AbsoluteHumidity,
     ApparentPower,
     Aqi,
+    Area,
     AtmosphericPressure,
     Battery,
+    BloodGlucoseConcentration,
     #[strum(to_string = "carbon_dioxide")]
+    #[serde(rename = "carbon_dioxide")]
     Co2,
     #[strum(to_string = "carbon_monoxide")]
+    #[serde(rename = "carbon_monoxide")]
     Co,
+    Conductivity,
     Current,
     DataRate,
     DataSize,
@@ -67,6 +140,7 @@ pub enum SensorClass {
     Distance,
     Duration,
     Energy,
+    EnergyDistance,
     EnergyStorage,
     Enum,
     Frequency,
@@ -87,8 +161,14 @@ pub enum SensorClass {
     Power,
     PowerFactor,
     Precipitation,
-    PrecipitationDensity,
+    #[strum(
+        to_string = "precipitation_intensity",
+        serialize = "precipitation_density"
+    )]
+    #[serde(alias = "precipitation_density")]
+    PrecipitationIntensity,
     Pressure,
+    ReactiveEnergy,
     ReactivePower,
     SignalStrength,
     SoundPressure,
@@ -97,12 +177,353 @@ pub enum SensorClass {
     Temperature,
     Timestamp,
     VolatileOrganicCompounds,
-    VolatileOrganicCompoundsParst,
+    #[strum(
+        to_string = "volatile_organic_compounds_parts",
+        serialize = "volatile_organic_compounds_parst"
+    )]
+    #[serde(alias = "volatile_organic_compounds_parst")]
+    VolatileOrganicCompoundsParts,
     Voltage,
     Volume,
     VolumeFlowRate,
     VolumeStorage,
     Water,
     Weight,
+    WindDirection,
     WindSpeed,
 }
+
+#[allow(non_upper_case_globals)]
+impl SensorClass {
+    #[deprecated(note = "renamed to `SensorClass::PrecipitationIntensity`")]
+    pub const PrecipitationDensity: Self = Self::PrecipitationIntensity;
+
+    #[deprecated(note = "renamed to `SensorClass::VolatileOrganicCompoundsParts`")]
+    pub const VolatileOrganicCompoundsParst: Self = Self::VolatileOrganicCompoundsParts;
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CoverClass {
+    Awning,
+    Blind,
+    Curtain,
+    Damper,
+    Door,
+    Garage,
+    Gate,
+    Shade,
+    Shutter,
+    Window,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ValveClass {
+    Gas,
+    Water,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    strum::AsRefStr,
+    strum::EnumString,
+    strum::VariantNames,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EventClass {
+    Button,
+    Doorbell,
+    Motion,
+}
+
+/// The device class of an entity.
+///
+/// Using the typed variants, the component the class belongs to is known. This allows to check
+/// if it fits the entity it is used with. The component specific builders, like
+/// [`crate::model::SensorDiscovery`], only accept the classes of their component, which is
+/// checked at compile time.
+///
+/// When deserializing, the component is not known. The class will be an [`DeviceClass::Other`],
+/// which can be resolved using [`DeviceClass::resolve`]. Classes are only equal if their variants
+/// are equal, so an unresolved class is not equal to its typed counterpart.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceClass {
+    BinarySensor(BinarySensorClass),
+    Button(ButtonClass),
+    Cover(CoverClass),
+    Event(EventClass),
+    Sensor(SensorClass),
+    Switch(SwitchClass),
+    Valve(ValveClass),
+    /// A class which is not known to this crate, or not resolved for a component.
+    Other(String),
+}
+
+impl DeviceClass {
+    /// Parse a class for the provided component.
+    pub fn parse(component: Component, value: &str) -> Option<Self> {
+        match component {
+            Component::BinarySensor => BinarySensorClass::from_str(value).ok().map(Self::from),
+            Component::Button => ButtonClass::from_str(value).ok().map(Self::from),
+            Component::Cover => CoverClass::from_str(value).ok().map(Self::from),
+            Component::Event => EventClass::from_str(value).ok().map(Self::from),
+            Component::Sensor => SensorClass::from_str(value).ok().map(Self::from),
+            Component::Switch => SwitchClass::from_str(value).ok().map(Self::from),
+            Component::Valve => ValveClass::from_str(value).ok().map(Self::from),
+            _ => None,
+        }
+    }
+
+    /// The component this class belongs to, `None` for [`DeviceClass::Other`].
+    pub fn component(&self) -> Option<Component> {
+        Some(match self {
+            Self::BinarySensor(_) => Component::BinarySensor,
+            Self::Button(_) => Component::Button,
+            Self::Cover(_) => Component::Cover,
+            Self::Event(_) => Component::Event,
+            Self::Sensor(_) => Component::Sensor,
+            Self::Switch(_) => Component::Switch,
+            Self::Valve(_) => Component::Valve,
+            Self::Other(_) => return None,
+        })
+    }
+
+    /// Resolve an [`DeviceClass::Other`] into a typed class of the provided component.
+    ///
+    /// If the class is unknown for the component, it stays an [`DeviceClass::Other`].
+    pub fn resolve(self, component: Component) -> Self {
+        match self {
+            Self::Other(value) => Self::parse(component, &value).unwrap_or(Self::Other(value)),
+            class => class,
+        }
+    }
+
+    /// Check if the class is a known class of the provided component.
+    pub fn is_valid_for(&self, component: Component) -> bool {
+        match self.component() {
+            Some(c) => c == component,
+            None => Self::parse(component, self.as_ref()).is_some(),
+        }
+    }
+}
+
+impl AsRef<str> for DeviceClass {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::BinarySensor(class) => class.as_ref(),
+            Self::Button(class) => class.as_ref(),
+            Self::Cover(class) => class.as_ref(),
+            Self::Event(class) => class.as_ref(),
+            Self::Sensor(class) => class.as_ref(),
+            Self::Switch(class) => class.as_ref(),
+            Self::Valve(class) => class.as_ref(),
+            Self::Other(class) => class,
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+impl From<BinarySensorClass> for DeviceClass {
+    fn from(value: BinarySensorClass) -> Self {
+        Self::BinarySensor(value)
+    }
+}
+
+impl From<ButtonClass> for DeviceClass {
+    fn from(value: ButtonClass) -> Self {
+        Self::Button(value)
+    }
+}
+
+impl From<CoverClass> for DeviceClass {
+    fn from(value: CoverClass) -> Self {
+        Self::Cover(value)
+    }
+}
+
+impl From<EventClass> for DeviceClass {
+    fn from(value: EventClass) -> Self {
+        Self::Event(value)
+    }
+}
+
+impl From<SensorClass> for DeviceClass {
+    fn from(value: SensorClass) -> Self {
+        Self::Sensor(value)
+    }
+}
+
+impl From<SwitchClass> for DeviceClass {
+    fn from(value: SwitchClass) -> Self {
+        Self::Switch(value)
+    }
+}
+
+impl From<ValveClass> for DeviceClass {
+    fn from(value: ValveClass) -> Self {
+        Self::Valve(value)
+    }
+}
+
+impl serde::Serialize for DeviceClass {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DeviceClass {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::Other)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for DeviceClass {
+    fn schema_name() -> String {
+        "DeviceClass".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::Discovery;
+    use serde::{de::DeserializeOwned, Serialize};
+    use strum::VariantNames;
+
+    fn assert_round_trip<C>(component: Component)
+    where
+        C: FromStr
+            + AsRef<str>
+            + VariantNames
+            + Serialize
+            + DeserializeOwned
+            + PartialEq
+            + Debug
+            + Copy
+            + Into<DeviceClass>,
+        C::Err: Debug,
+    {
+        for name in C::VARIANTS {
+            let class = C::from_str(name).unwrap();
+            assert_eq!(class.as_ref(), *name);
+            assert_eq!(serde_json::to_value(class).unwrap(), *name);
+            assert_eq!(serde_json::from_value::<C>((*name).into()).unwrap(), class);
+
+            let json = serde_json::to_string(&Discovery {
+                device_class: Some(class.into()),
+                ..Default::default()
+            })
+            .unwrap();
+            let discovery: Discovery = serde_json::from_str(&json).unwrap();
+            let device_class = discovery.device_class.unwrap();
+            assert_eq!(device_class, DeviceClass::Other(name.to_string()));
+            assert!(device_class.is_valid_for(component));
+
+            let device_class = device_class.resolve(component);
+            assert_eq!(device_class, class.into());
+            assert_eq!(device_class.component(), Some(component));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_round_trip::<BinarySensorClass>(Component::BinarySensor);
+        assert_round_trip::<ButtonClass>(Component::Button);
+        assert_round_trip::<CoverClass>(Component::Cover);
+        assert_round_trip::<EventClass>(Component::Event);
+        assert_round_trip::<SensorClass>(Component::Sensor);
+        assert_round_trip::<SwitchClass>(Component::Switch);
+        assert_round_trip::<ValveClass>(Component::Valve);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_renamed() {
+        assert_eq!(
+            SensorClass::from_str("precipitation_density").unwrap(),
+            SensorClass::PrecipitationDensity
+        );
+        assert_eq!(
+            serde_json::from_value::<SensorClass>("volatile_organic_compounds_parst".into())
+                .unwrap(),
+            SensorClass::VolatileOrganicCompoundsParts
+        );
+        assert_eq!(
+            SensorClass::PrecipitationDensity.as_ref(),
+            "precipitation_intensity"
+        );
+    }
+
+    #[test]
+    fn test_component() {
+        let class = DeviceClass::from(BinarySensorClass::Motion);
+        assert!(class.is_valid_for(Component::BinarySensor));
+        assert!(!class.is_valid_for(Component::Sensor));
+
+        let class = DeviceClass::Other("temprature".into());
+        assert!(!class.is_valid_for(Component::Sensor));
+        assert_eq!(
+            class.resolve(Component::Sensor),
+            DeviceClass::Other("temprature".into())
+        );
+    }
+
+    #[test]
+    fn test_eq() {
+        let battery = DeviceClass::from(SensorClass::Battery);
+        assert_ne!(battery, DeviceClass::from(BinarySensorClass::Battery));
+        assert_ne!(battery, DeviceClass::Other("battery".into()));
+        assert_eq!(
+            DeviceClass::Other("battery".into()).resolve(Component::Sensor),
+            battery
+        );
+    }
+}
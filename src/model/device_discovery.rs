@@ -0,0 +1,160 @@
+use crate::model::{Component, Device, Discovery, Origin};
+use std::collections::BTreeMap;
+
+// also see: https://www.home-assistant.io/integrations/mqtt/#device-discovery-payload
+
+/// Discovery message of a device, with all of its components.
+///
+/// All components are announced with a single message. Removing this message removes all
+/// components at once.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceDiscovery {
+    #[serde(alias = "dev")]
+    pub device: Device,
+
+    #[serde(alias = "o")]
+    pub origin: Origin,
+
+    /// The components, by their object ID.
+    #[serde(alias = "cmps")]
+    #[serde(default)]
+    pub components: BTreeMap<String, DeviceComponent>,
+}
+
+/// A component of a [`DeviceDiscovery`].
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceComponent {
+    #[serde(alias = "p")]
+    pub platform: Component,
+
+    /// The discovery information of the component, which must have a unique ID. The device is
+    /// taken from the device discovery message.
+    #[serde(flatten)]
+    pub discovery: Discovery,
+}
+
+impl DeviceDiscovery {
+    pub fn new(device: Device, origin: Origin) -> Self {
+        Self {
+            device,
+            origin,
+            components: Default::default(),
+        }
+    }
+
+    /// Add a component, replacing an existing one with the same object ID.
+    pub fn component(
+        mut self,
+        object_id: impl Into<String>,
+        platform: Component,
+        discovery: impl Into<Discovery>,
+    ) -> Self {
+        self.components.insert(
+            object_id.into(),
+            DeviceComponent {
+                platform,
+                discovery: discovery.into(),
+            },
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{DeviceDiscoveryId, KeyFormat, SensorDiscovery, SwitchDiscovery};
+    use serde_json::json;
+
+    fn discovery() -> DeviceDiscovery {
+        DeviceDiscovery::new(
+            Device::new("device-1").name("Device 1"),
+            Origin::new("agent").sw_version("1.0"),
+        )
+        .component(
+            "switch-1",
+            Component::Switch,
+            Discovery {
+                unique_id: Some("device-1-switch-1".into()),
+                ..SwitchDiscovery::new("device-1/switch-1/set").build()
+            },
+        )
+        .component(
+            "sensor-1",
+            Component::Sensor,
+            Discovery {
+                unique_id: Some("device-1-sensor-1".into()),
+                ..SensorDiscovery::new("device-1/sensor-1").build()
+            },
+        )
+    }
+
+    #[test]
+    fn test_serde() {
+        let value = serde_json::to_value(discovery()).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "device": {
+                    "identifiers": ["device-1"],
+                    "name": "Device 1",
+                },
+                "origin": {
+                    "name": "agent",
+                    "sw_version": "1.0",
+                },
+                "components": {
+                    "sensor-1": {
+                        "platform": "sensor",
+                        "name": null,
+                        "unique_id": "device-1-sensor-1",
+                        "device_class": null,
+                        "state_topic": "device-1/sensor-1",
+                    },
+                    "switch-1": {
+                        "platform": "switch",
+                        "name": null,
+                        "unique_id": "device-1-switch-1",
+                        "device_class": null,
+                        "command_topic": "device-1/switch-1/set",
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<DeviceDiscovery>(value).unwrap(),
+            discovery()
+        );
+    }
+
+    #[test]
+    fn test_abbreviated() {
+        let value = discovery().to_value(KeyFormat::Abbreviated).unwrap();
+        assert_eq!(value["dev"]["ids"], json!(["device-1"]));
+        assert_eq!(value["o"]["sw"], json!("1.0"));
+        assert_eq!(value["cmps"]["switch-1"]["p"], json!("switch"));
+        assert_eq!(
+            value["cmps"]["switch-1"]["cmd_t"],
+            json!("device-1/switch-1/set")
+        );
+
+        assert_eq!(
+            serde_json::from_value::<DeviceDiscovery>(value).unwrap(),
+            discovery()
+        );
+    }
+
+    #[test]
+    fn test_config_topic() {
+        assert_eq!(
+            DeviceDiscoveryId::new("device-1").config_topic(),
+            "device/device-1/config"
+        );
+        assert_eq!(
+            DeviceDiscoveryId::with_node_id("device-1", "agent").config_topic(),
+            "device/agent/device-1/config"
+        );
+    }
+}
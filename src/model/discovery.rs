@@ -1,65 +1,517 @@
-use crate::{model::Device, utils::is_default};
+use crate::{
+    model::{
+        ColorMode, Device, DeviceClass, HvacMode, InputMode, LightSchema, Origin, TemperatureUnit,
+        TextPattern,
+    },
+    utils::is_default,
+};
 
 // also see: https://developers.home-assistant.io/docs/core/entity/
 
 /// Discovery message
-#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "Discovery"))]
+#[serde(remote = "Self")]
 pub struct Discovery {
-    /// The name of the application that is the origin the discovered MQTT item. This option is required.
+    /// The name of the entity. If it is `null`, only the name of the device is used.
     // Don't skip serde if it's empty, as it has to be null then
     #[serde(default)]
     pub name: Option<String>,
 
+    /// The base topic, which replaces a `~` at the start or end of all topics.
+    ///
+    /// Also see [`Discovery::compress_topics`] and [`Discovery::expand_topics`].
+    #[serde(rename = "~")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_topic: Option<String>,
+
+    #[serde(alias = "uniq_id")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub unique_id: Option<String>,
 
+    #[serde(alias = "dev")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub device: Option<Device>,
 
+    /// The application publishing the discovery message.
+    #[serde(alias = "o")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Origin>,
+
     /// The device class. Should be `null` if omitted, so don't skip.
+    #[serde(alias = "dev_cla")]
     #[serde(default)]
-    pub device_class: Option<String>,
+    pub device_class: Option<DeviceClass>,
 
+    #[serde(alias = "stat_cla")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state_class: Option<StateClass>,
 
+    #[serde(alias = "cmd_t")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub command_topic: Option<String>,
 
+    #[serde(alias = "cmd_tpl")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub command_template: Option<String>,
 
+    #[serde(alias = "stat_t")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state_topic: Option<String>,
 
+    #[serde(alias = "unit_of_meas")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub unit_of_measurement: Option<String>,
 
+    /// The number of decimals used for the state of a sensor.
+    #[serde(alias = "sug_dsp_prc")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_display_precision: Option<u32>,
+
+    #[serde(alias = "val_tpl")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value_template: Option<String>,
 
+    #[serde(alias = "en")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enabled_by_default: Option<bool>,
 
+    #[serde(alias = "avty_mode")]
     #[serde(default, skip_serializing_if = "is_default")]
     pub availability_mode: AvailabilityMode,
 
+    #[serde(alias = "avty")]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub availability: Vec<Availability>,
+
+    /// The topic additional attributes of the entity are published on, as a JSON object.
+    #[serde(alias = "json_attr_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_attributes_topic: Option<String>,
+
+    /// A template extracting the attributes object from the message.
+    #[serde(alias = "json_attr_tpl")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_attributes_template: Option<String>,
+
+    // light
+    /// The schema of a light, also see [`crate::model::LightDiscovery`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<LightSchema>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<bool>,
+
+    /// The maximum brightness value, defaults to `255`.
+    #[serde(alias = "bri_scl")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness_scale: Option<u32>,
+
+    #[serde(alias = "sup_clrm")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supported_color_modes: Vec<ColorMode>,
+
+    /// Use kelvin instead of mireds for color temperatures.
+    #[serde(alias = "clr_temp_k")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_temp_kelvin: Option<bool>,
+
+    #[serde(alias = "min_k")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_kelvin: Option<u32>,
+
+    #[serde(alias = "max_k")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_kelvin: Option<u32>,
+
+    #[serde(alias = "min_mirs")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_mireds: Option<u32>,
+
+    #[serde(alias = "max_mirs")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_mireds: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<bool>,
+
+    #[serde(alias = "fx_list")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effect_list: Vec<String>,
+
+    /// Duration of a short flash, in seconds.
+    #[serde(alias = "flsh_tsht")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flash_time_short: Option<u32>,
+
+    /// Duration of a long flash, in seconds.
+    #[serde(alias = "flsh_tlng")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flash_time_long: Option<u32>,
+
+    // climate
+    #[serde(alias = "mode_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_command_topic: Option<String>,
+
+    #[serde(alias = "mode_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_state_topic: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modes: Vec<HvacMode>,
+
+    #[serde(alias = "temp_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_command_topic: Option<String>,
+
+    #[serde(alias = "temp_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_state_topic: Option<String>,
+
+    #[serde(alias = "temp_lo_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_low_command_topic: Option<String>,
+
+    #[serde(alias = "temp_lo_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_low_state_topic: Option<String>,
+
+    #[serde(alias = "temp_hi_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_high_command_topic: Option<String>,
+
+    #[serde(alias = "temp_hi_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_high_state_topic: Option<String>,
+
+    #[serde(alias = "curr_temp_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_temperature_topic: Option<String>,
+
+    #[serde(alias = "temp_unit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_unit: Option<TemperatureUnit>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_temp: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_temp: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_step: Option<f64>,
+
+    /// The precision of the temperature, `0.1`, `0.5` or `1.0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+
+    #[serde(alias = "act_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_topic: Option<String>,
+
+    #[serde(alias = "pr_mode_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_mode_command_topic: Option<String>,
+
+    #[serde(alias = "pr_mode_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_mode_state_topic: Option<String>,
+
+    #[serde(alias = "pr_modes")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preset_modes: Vec<String>,
+
+    #[serde(alias = "fan_mode_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_mode_command_topic: Option<String>,
+
+    #[serde(alias = "fan_mode_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_mode_state_topic: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fan_modes: Vec<String>,
+
+    // cover
+    #[serde(alias = "pl_open")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_open: Option<String>,
+
+    #[serde(alias = "pl_cls")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_close: Option<String>,
+
+    #[serde(alias = "pl_stop")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_stop: Option<String>,
+
+    #[serde(alias = "stat_open")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_open: Option<String>,
+
+    #[serde(alias = "stat_opening")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_opening: Option<String>,
+
+    #[serde(alias = "stat_clsd")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_closed: Option<String>,
+
+    #[serde(alias = "stat_closing")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_closing: Option<String>,
+
+    #[serde(alias = "stat_stopped")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_stopped: Option<String>,
+
+    #[serde(alias = "pos_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_topic: Option<String>,
+
+    #[serde(alias = "set_pos_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_position_topic: Option<String>,
+
+    /// The position reported when fully open, defaults to `100`.
+    #[serde(alias = "pos_open")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_open: Option<i64>,
+
+    /// The position reported when fully closed, defaults to `0`.
+    #[serde(alias = "pos_clsd")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_closed: Option<i64>,
+
+    #[serde(alias = "tilt_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_command_topic: Option<String>,
+
+    #[serde(alias = "tilt_status_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_status_topic: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_min: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_max: Option<i64>,
+
+    #[serde(alias = "tilt_opnd_val")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_opened_value: Option<i64>,
+
+    #[serde(alias = "tilt_clsd_val")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tilt_closed_value: Option<i64>,
+
+    // number, select, text
+    /// The minimum value of a number, or the minimum length of a text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+
+    /// The maximum value of a number, or the maximum length of a text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<InputMode>,
+
+    /// The options of a select, or an `enum` sensor.
+    #[serde(alias = "ops")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+
+    /// A regular expression a text has to match.
+    #[serde(alias = "ptrn")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<TextPattern>,
+
+    // button
+    #[serde(alias = "pl_prs")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_press: Option<String>,
+
+    // lock
+    #[serde(alias = "pl_lock")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_lock: Option<String>,
+
+    #[serde(alias = "pl_unlk")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_unlock: Option<String>,
+
+    #[serde(alias = "stat_locked")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_locked: Option<String>,
+
+    #[serde(alias = "stat_locking")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_locking: Option<String>,
+
+    #[serde(alias = "stat_unlocked")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_unlocked: Option<String>,
+
+    #[serde(alias = "stat_unlocking")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_unlocking: Option<String>,
+
+    #[serde(alias = "stat_jam")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_jammed: Option<String>,
+
+    // valve
+    /// The valve reports and accepts a position, instead of open/close payloads.
+    #[serde(alias = "pos")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reports_position: Option<bool>,
+
+    // fan
+    #[serde(alias = "pl_on")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_on: Option<String>,
+
+    #[serde(alias = "pl_off")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_off: Option<String>,
+
+    /// The state payload for on, defaults to `payload_on`.
+    #[serde(alias = "stat_on")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_on: Option<String>,
+
+    /// The state payload for off, defaults to `payload_off`.
+    #[serde(alias = "stat_off")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_off: Option<String>,
+
+    #[serde(alias = "pct_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage_command_topic: Option<String>,
+
+    #[serde(alias = "pct_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage_state_topic: Option<String>,
+
+    #[serde(alias = "spd_rng_min")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_range_min: Option<i64>,
+
+    #[serde(alias = "spd_rng_max")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_range_max: Option<i64>,
+
+    #[serde(alias = "osc_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oscillation_command_topic: Option<String>,
+
+    #[serde(alias = "osc_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oscillation_state_topic: Option<String>,
+
+    #[serde(alias = "pl_osc_on")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_oscillation_on: Option<String>,
+
+    #[serde(alias = "pl_osc_off")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_oscillation_off: Option<String>,
+
+    #[serde(alias = "dir_cmd_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction_command_topic: Option<String>,
+
+    #[serde(alias = "dir_stat_t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction_state_topic: Option<String>,
+
+    // device trigger
+    /// The type of automation, must be `trigger`.
+    #[serde(alias = "atype")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automation_type: Option<String>,
+
+    /// The topic of a device trigger.
+    #[serde(alias = "t")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+
+    /// The type of device trigger, like `button_short_press`.
+    #[serde(rename = "type")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger_type: Option<String>,
+
+    /// The subtype of a device trigger, like `button_1`.
+    #[serde(alias = "stype")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+
+    /// The payload which fires a device trigger.
+    #[serde(alias = "pl")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+
+    // event
+    #[serde(alias = "evt_typ")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<String>,
+}
+
+impl serde::Serialize for Discovery {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Fields<'a>(&'a Discovery);
+
+        impl serde::Serialize for Fields<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                Discovery::serialize(self.0, serializer)
+            }
+        }
+
+        if self.automation_type.is_none() {
+            return Discovery::serialize(self, serializer);
+        }
+
+        // device triggers don't accept the keys, which have to be `null` for entities
+        let mut map = match serde_json::to_value(Fields(self)).map_err(serde::ser::Error::custom)? {
+            serde_json::Value::Object(map) => map,
+            value => return value.serialize(serializer),
+        };
+        for key in ["name", "device_class"] {
+            if map.get(key).is_some_and(serde_json::Value::is_null) {
+                map.remove(key);
+            }
+        }
+        map.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Discovery {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Discovery::deserialize(deserializer)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Availability {
+    #[serde(alias = "t")]
     pub topic: String,
 
+    #[serde(alias = "pl_avail")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub payload_available: Option<String>,
 
+    #[serde(alias = "pl_not_avail")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub payload_not_available: Option<String>,
 
+    #[serde(alias = "val_tpl")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value_template: Option<String>,
 }
@@ -100,11 +552,12 @@ pub enum AvailabilityMode {
     Latest,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum StateClass {
     Measurement,
+    MeasurementAngle,
     Total,
     TotalIncreasing,
 }
@@ -112,20 +565,15 @@ pub enum StateClass {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::model::BinarySensorClass;
     use serde_json::json;
 
     #[test]
     fn test_serde() {
         assert_eq!(
             serde_json::to_value(Discovery {
-                device: Some(Device {
-                    identifiers: vec!["test-id1".into()],
-                    name: Some("Test Device 1".to_string()),
-                    base_topic: None,
-                    sw_version: None,
-                    support_url: None,
-                }),
-                device_class: Some("motion".to_string()),
+                device: Some(Device::new("test-id1").name("Test Device 1")),
+                device_class: Some(BinarySensorClass::Motion.into()),
                 state_topic: Some("some/topic".to_string()),
                 ..Default::default()
             })
@@ -0,0 +1,164 @@
+use crate::model::{Device, Discovery, EventClass};
+
+// also see:
+//  * https://www.home-assistant.io/integrations/device_trigger.mqtt/
+//  * https://www.home-assistant.io/integrations/event.mqtt/
+
+/// Builder for a device trigger.
+///
+/// Device triggers are announced using [`crate::model::Component::DeviceAutomation`] and
+/// require a device. They can be fired using [`crate::connector::Client::fire_trigger`].
+#[derive(Clone, Debug)]
+pub struct DeviceTriggerDiscovery {
+    discovery: Discovery,
+}
+
+impl DeviceTriggerDiscovery {
+    /// Create a new trigger of a device, using a `trigger_type` like `button_short_press` and a
+    /// `subtype` like `button_1`.
+    pub fn new(
+        device: Device,
+        topic: impl Into<String>,
+        trigger_type: impl Into<String>,
+        subtype: impl Into<String>,
+    ) -> Self {
+        Self {
+            discovery: Discovery {
+                device: Some(device),
+                automation_type: Some("trigger".to_string()),
+                topic: Some(topic.into()),
+                trigger_type: Some(trigger_type.into()),
+                subtype: Some(subtype.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Only fire the trigger when the provided payload is received.
+    pub fn payload(mut self, payload: impl Into<String>) -> Self {
+        self.discovery.payload = Some(payload.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<DeviceTriggerDiscovery> for Discovery {
+    fn from(value: DeviceTriggerDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// Builder for an event entity.
+///
+/// Events can be fired using [`crate::connector::Client::fire_event`].
+#[derive(Clone, Debug)]
+pub struct EventDiscovery {
+    discovery: Discovery,
+}
+
+impl EventDiscovery {
+    pub fn new<I>(state_topic: impl Into<String>, event_types: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            discovery: Discovery {
+                state_topic: Some(state_topic.into()),
+                event_types: event_types.into_iter().map(Into::into).collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: EventClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<EventDiscovery> for Discovery {
+    fn from(value: EventDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// An event, fired by an event entity.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Event {
+    /// The type of the event, one of the announced event types.
+    pub event_type: String,
+
+    /// Additional attributes of the event.
+    #[serde(flatten)]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Event {
+    pub fn new(event_type: impl Into<String>) -> Self {
+        Self {
+            event_type: event_type.into(),
+            attributes: Default::default(),
+        }
+    }
+
+    pub fn attribute(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::KeyFormat;
+    use serde_json::json;
+
+    #[test]
+    fn test_trigger() {
+        let discovery = DeviceTriggerDiscovery::new(
+            Device::new("remote-1"),
+            "remote/action",
+            "button_short_press",
+            "button_1",
+        )
+        .payload("single")
+        .build();
+
+        let expected = json!({
+            "device": {
+                "identifiers": ["remote-1"],
+                "name": null,
+            },
+            "automation_type": "trigger",
+            "topic": "remote/action",
+            "type": "button_short_press",
+            "subtype": "button_1",
+            "payload": "single",
+        });
+        assert_eq!(serde_json::to_value(&discovery).unwrap(), expected);
+        assert_eq!(discovery.to_value(KeyFormat::Full).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_event() {
+        assert_eq!(
+            serde_json::to_value(Event::new("press").attribute("button", 1)).unwrap(),
+            json!({
+                "event_type": "press",
+                "button": 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::model::{
+    is_payload, is_topic, parse_integer, parse_str, Command, CommandError, Discovery,
+};
+
+// also see: https://www.home-assistant.io/integrations/fan.mqtt/
+
+const DEFAULT_SPEED_RANGE_MIN: i64 = 1;
+const DEFAULT_SPEED_RANGE_MAX: i64 = 100;
+
+/// Builder for a fan.
+///
+/// Commands received on the command topics of the fan can be decoded into a [`FanCommand`].
+#[derive(Clone, Debug)]
+pub struct FanDiscovery {
+    discovery: Discovery,
+}
+
+impl FanDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Override the default command payloads (`ON`, `OFF`).
+    pub fn payloads(mut self, on: impl Into<String>, off: impl Into<String>) -> Self {
+        self.discovery.payload_on = Some(on.into());
+        self.discovery.payload_off = Some(off.into());
+        self
+    }
+
+    /// Enable setting the speed, in the range of `min` to `max`.
+    pub fn percentage(mut self, command_topic: impl Into<String>, min: i64, max: i64) -> Self {
+        self.discovery.percentage_command_topic = Some(command_topic.into());
+        self.discovery.speed_range_min = Some(min);
+        self.discovery.speed_range_max = Some(max);
+        self
+    }
+
+    pub fn percentage_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.percentage_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable preset modes, using the provided list.
+    pub fn preset_modes<I>(mut self, command_topic: impl Into<String>, presets: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.discovery.preset_mode_command_topic = Some(command_topic.into());
+        self.discovery.preset_modes = presets.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn preset_mode_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.preset_mode_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable oscillation.
+    pub fn oscillation(mut self, command_topic: impl Into<String>) -> Self {
+        self.discovery.oscillation_command_topic = Some(command_topic.into());
+        self
+    }
+
+    pub fn oscillation_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.oscillation_state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable setting the direction.
+    pub fn direction(mut self, command_topic: impl Into<String>) -> Self {
+        self.discovery.direction_command_topic = Some(command_topic.into());
+        self
+    }
+
+    pub fn direction_state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.direction_state_topic = Some(topic.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<FanDiscovery> for Discovery {
+    fn from(value: FanDiscovery) -> Self {
+        value.build()
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumString, strum::VariantNames,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum FanDirection {
+    Forward,
+    Reverse,
+}
+
+/// A command for a fan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FanCommand {
+    TurnOn,
+    TurnOff,
+    /// Set the speed, in the range of `speed_range_min` to `speed_range_max`.
+    SetPercentage(i64),
+    SetPresetMode(String),
+    SetOscillation(bool),
+    SetDirection(FanDirection),
+}
+
+impl Command for FanCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        let command = if is_topic(&discovery.command_topic, topic) {
+            if is_payload(payload, &discovery.payload_on, "ON") {
+                Ok(Self::TurnOn)
+            } else if is_payload(payload, &discovery.payload_off, "OFF") {
+                Ok(Self::TurnOff)
+            } else {
+                Err(CommandError::invalid_payload(payload))
+            }
+        } else if is_topic(&discovery.percentage_command_topic, topic) {
+            parse_integer(payload).and_then(|value| {
+                let min = discovery.speed_range_min.unwrap_or(DEFAULT_SPEED_RANGE_MIN);
+                let max = discovery.speed_range_max.unwrap_or(DEFAULT_SPEED_RANGE_MAX);
+                // zero is used for turning off the fan
+                if value == 0 || (min..=max).contains(&value) {
+                    Ok(Self::SetPercentage(value))
+                } else {
+                    Err(CommandError::OutOfRange {
+                        value: value as f64,
+                        min: min as f64,
+                        max: max as f64,
+                    })
+                }
+            })
+        } else if is_topic(&discovery.preset_mode_command_topic, topic) {
+            parse_str(payload).and_then(|preset| {
+                if discovery.preset_modes.iter().any(|p| p == preset) {
+                    Ok(Self::SetPresetMode(preset.to_string()))
+                } else {
+                    Err(CommandError::UnknownOption(preset.to_string()))
+                }
+            })
+        } else if is_topic(&discovery.oscillation_command_topic, topic) {
+            if is_payload(payload, &discovery.payload_oscillation_on, "oscillate_on") {
+                Ok(Self::SetOscillation(true))
+            } else if is_payload(payload, &discovery.payload_oscillation_off, "oscillate_off") {
+                Ok(Self::SetOscillation(false))
+            } else {
+                Err(CommandError::invalid_payload(payload))
+            }
+        } else if is_topic(&discovery.direction_command_topic, topic) {
+            parse_str(payload).and_then(|direction| {
+                direction
+                    .parse()
+                    .map(Self::SetDirection)
+                    .map_err(|_| CommandError::invalid_payload(payload))
+            })
+        } else {
+            return None;
+        };
+
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let discovery = FanDiscovery::new("fan/set")
+            .percentage("fan/speed/set", 1, 3)
+            .preset_modes("fan/preset/set", ["auto", "sleep"])
+            .oscillation("fan/oscillation/set")
+            .build();
+
+        assert_eq!(
+            FanCommand::decode(&discovery, "fan/set", b"ON")
+                .unwrap()
+                .unwrap(),
+            FanCommand::TurnOn
+        );
+        assert_eq!(
+            FanCommand::decode(&discovery, "fan/speed/set", b"2")
+                .unwrap()
+                .unwrap(),
+            FanCommand::SetPercentage(2)
+        );
+        assert!(matches!(
+            FanCommand::decode(&discovery, "fan/speed/set", b"4"),
+            Some(Err(CommandError::OutOfRange { .. }))
+        ));
+        assert!(matches!(
+            FanCommand::decode(&discovery, "fan/preset/set", b"turbo"),
+            Some(Err(CommandError::UnknownOption(_)))
+        ));
+        assert_eq!(
+            FanCommand::decode(&discovery, "fan/oscillation/set", b"oscillate_off")
+                .unwrap()
+                .unwrap(),
+            FanCommand::SetOscillation(false)
+        );
+    }
+}
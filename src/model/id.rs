@@ -16,7 +16,7 @@ impl DeviceId {
             node_id: None,
         }
     }
-    pub fn with_node_id<I, C>(
+    pub fn with_node_id(
         id: impl Into<Cow<'static, str>>,
         component: Component,
         node_id: impl Into<Cow<'static, str>>,
@@ -30,12 +30,71 @@ impl DeviceId {
 
     /// render the config topic
     pub fn config_topic(&self) -> String {
-        format!(
-            "{component}/{node_id}{node_id_slash}{object_id}/config",
-            component = self.component,
-            object_id = self.id,
-            node_id_slash = if self.node_id.is_some() { "/" } else { "" },
-            node_id = self.node_id.as_deref().unwrap_or(""),
-        )
+        config_topic(self.component.as_ref(), self.node_id.as_deref(), &self.id)
+    }
+
+    /// render a unique ID, which includes the node and component, as the ID alone is only
+    /// unique per component
+    pub fn unique_id(&self) -> String {
+        match &self.node_id {
+            Some(node_id) => format!("{node_id}_{}_{}", self.component.as_ref(), self.id),
+            None => format!("{}_{}", self.component.as_ref(), self.id),
+        }
+    }
+}
+
+/// The ID of a device, announced using a single [`crate::model::DeviceDiscovery`] message.
+#[derive(Clone, Debug)]
+pub struct DeviceDiscoveryId {
+    pub id: Cow<'static, str>,
+    pub node_id: Option<Cow<'static, str>>,
+}
+
+impl DeviceDiscoveryId {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            id: id.into(),
+            node_id: None,
+        }
+    }
+
+    pub fn with_node_id(
+        id: impl Into<Cow<'static, str>>,
+        node_id: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            node_id: Some(node_id.into()),
+        }
+    }
+
+    /// render the config topic, which is not specific to a component
+    pub fn config_topic(&self) -> String {
+        config_topic("device", self.node_id.as_deref(), &self.id)
+    }
+}
+
+fn config_topic(component: &str, node_id: Option<&str>, object_id: &str) -> String {
+    format!(
+        "{component}/{node_id}{node_id_slash}{object_id}/config",
+        node_id_slash = if node_id.is_some() { "/" } else { "" },
+        node_id = node_id.unwrap_or(""),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unique_id() {
+        assert_eq!(
+            DeviceId::new("x", Component::Sensor).unique_id(),
+            "sensor_x"
+        );
+        assert_eq!(
+            DeviceId::with_node_id("x", Component::Switch, "node").unique_id(),
+            "node_switch_x"
+        );
     }
 }
@@ -0,0 +1,474 @@
+use crate::model::{is_topic, parse_number, parse_str, Command, CommandError, Discovery};
+use regex::Regex;
+use std::sync::OnceLock;
+
+// also see:
+//  * https://www.home-assistant.io/integrations/number.mqtt/
+//  * https://www.home-assistant.io/integrations/select.mqtt/
+//  * https://www.home-assistant.io/integrations/text.mqtt/
+
+const DEFAULT_NUMBER_MIN: f64 = 1.0;
+const DEFAULT_NUMBER_MAX: f64 = 100.0;
+const DEFAULT_NUMBER_STEP: f64 = 1.0;
+const DEFAULT_TEXT_MIN: usize = 0;
+const DEFAULT_TEXT_MAX: usize = 255;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NumberMode {
+    Auto,
+    Box,
+    Slider,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TextMode {
+    Text,
+    Password,
+}
+
+/// The display mode of a number or text entity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum InputMode {
+    Number(NumberMode),
+    Text(TextMode),
+}
+
+impl From<NumberMode> for InputMode {
+    fn from(value: NumberMode) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<TextMode> for InputMode {
+    fn from(value: TextMode) -> Self {
+        Self::Text(value)
+    }
+}
+
+/// A regular expression a text has to match.
+///
+/// The expression is compiled once, when the first command gets validated against it.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct TextPattern {
+    pattern: String,
+    #[serde(skip)]
+    #[cfg_attr(feature = "schemars", schemars(skip))]
+    regex: OnceLock<Result<Regex, regex::Error>>,
+}
+
+impl TextPattern {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            regex: OnceLock::new(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The compiled regular expression.
+    pub fn regex(&self) -> Result<&Regex, regex::Error> {
+        self.regex
+            // Home Assistant uses Python's `re.match`, which is anchored at the start
+            .get_or_init(|| Regex::new(&format!("^(?:{})", self.pattern)))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+}
+
+impl PartialEq for TextPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for TextPattern {}
+
+impl From<String> for TextPattern {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for TextPattern {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Builder for a number.
+///
+/// Commands can be decoded into a [`NumberCommand`], which checks the announced range.
+#[derive(Clone, Debug)]
+pub struct NumberDiscovery {
+    discovery: Discovery,
+}
+
+impl NumberDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Set the range of the value, defaults to `1` to `100`.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.discovery.min = Some(min);
+        self.discovery.max = Some(max);
+        self
+    }
+
+    /// Set the step size, defaults to `1`.
+    pub fn step(mut self, step: f64) -> Self {
+        self.discovery.step = Some(step);
+        self
+    }
+
+    pub fn mode(mut self, mode: NumberMode) -> Self {
+        self.discovery.mode = Some(mode.into());
+        self
+    }
+
+    pub fn unit_of_measurement(mut self, unit: impl Into<String>) -> Self {
+        self.discovery.unit_of_measurement = Some(unit.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<NumberDiscovery> for Discovery {
+    fn from(value: NumberDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// Builder for a select.
+///
+/// Commands can be decoded into a [`SelectCommand`], which checks the announced options.
+#[derive(Clone, Debug)]
+pub struct SelectDiscovery {
+    discovery: Discovery,
+}
+
+impl SelectDiscovery {
+    pub fn new<I>(command_topic: impl Into<String>, options: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                options: options.into_iter().map(Into::into).collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<SelectDiscovery> for Discovery {
+    fn from(value: SelectDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// Builder for a text.
+///
+/// Commands can be decoded into a [`TextCommand`], which checks the announced length and
+/// pattern.
+#[derive(Clone, Debug)]
+pub struct TextDiscovery {
+    discovery: Discovery,
+}
+
+impl TextDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Set the range of the length, defaults to `0` to `255`.
+    pub fn length(mut self, min: usize, max: usize) -> Self {
+        self.discovery.min = Some(min as f64);
+        self.discovery.max = Some(max as f64);
+        self
+    }
+
+    /// Set a regular expression the value must match.
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.discovery.pattern = Some(TextPattern::new(pattern));
+        self
+    }
+
+    pub fn mode(mut self, mode: TextMode) -> Self {
+        self.discovery.mode = Some(mode.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<TextDiscovery> for Discovery {
+    fn from(value: TextDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// A new value for a number, within the announced range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NumberCommand(pub f64);
+
+impl Command for NumberCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(parse_number(payload).and_then(|value| {
+            let min = discovery.min.unwrap_or(DEFAULT_NUMBER_MIN);
+            let max = discovery.max.unwrap_or(DEFAULT_NUMBER_MAX);
+            let step = discovery.step.unwrap_or(DEFAULT_NUMBER_STEP);
+            if !(min..=max).contains(&value) {
+                Err(CommandError::OutOfRange { value, min, max })
+            } else if !Self::is_on_step(value, min, step) {
+                Err(CommandError::InvalidStep { value, min, step })
+            } else {
+                Ok(Self(value))
+            }
+        }))
+    }
+}
+
+impl NumberCommand {
+    /// Check if the value is a multiple of the step, starting at the minimum.
+    ///
+    /// Allows for some rounding error, as the values are floating point numbers.
+    fn is_on_step(value: f64, min: f64, step: f64) -> bool {
+        if step <= 0.0 {
+            return true;
+        }
+        let steps = (value - min) / step;
+        (steps - steps.round()).abs() <= 1e-9 * steps.abs().max(1.0)
+    }
+}
+
+/// A selected option, one of the announced options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectCommand(pub String);
+
+impl Command for SelectCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(parse_str(payload).and_then(|option| {
+            if discovery.options.iter().any(|o| o == option) {
+                Ok(Self(option.to_string()))
+            } else {
+                Err(CommandError::UnknownOption(option.to_string()))
+            }
+        }))
+    }
+}
+
+/// A new value for a text, matching the announced length and pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextCommand(pub String);
+
+impl TextCommand {
+    fn validate(discovery: &Discovery, value: &str) -> Result<(), CommandError> {
+        let length = value.chars().count();
+        let min = discovery
+            .min
+            .map(|min| min as usize)
+            .unwrap_or(DEFAULT_TEXT_MIN);
+        let max = discovery
+            .max
+            .map(|max| max as usize)
+            .unwrap_or(DEFAULT_TEXT_MAX);
+        if !(min..=max).contains(&length) {
+            return Err(CommandError::InvalidLength { length, min, max });
+        }
+
+        if let Some(pattern) = &discovery.pattern {
+            if !pattern.regex()?.is_match(value) {
+                return Err(CommandError::PatternMismatch {
+                    value: value.to_string(),
+                    pattern: pattern.as_str().to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Command for TextCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(parse_str(payload).and_then(|value| {
+            Self::validate(discovery, value)?;
+            Ok(Self(value.to_string()))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let discovery = NumberDiscovery::new("number/set").range(5.0, 60.0).build();
+
+        assert_eq!(
+            NumberCommand::decode(&discovery, "number/set", b"30")
+                .unwrap()
+                .unwrap(),
+            NumberCommand(30.0)
+        );
+        assert!(matches!(
+            NumberCommand::decode(&discovery, "number/set", b"61"),
+            Some(Err(CommandError::OutOfRange { .. }))
+        ));
+        assert!(matches!(
+            NumberCommand::decode(&discovery, "number/set", b"30.5"),
+            Some(Err(CommandError::InvalidStep { .. }))
+        ));
+
+        let discovery = NumberDiscovery::new("number/set")
+            .range(0.5, 2.0)
+            .step(0.1)
+            .build();
+        assert_eq!(
+            NumberCommand::decode(&discovery, "number/set", b"1.7")
+                .unwrap()
+                .unwrap(),
+            NumberCommand(1.7)
+        );
+        assert!(matches!(
+            NumberCommand::decode(&discovery, "number/set", b"1.75"),
+            Some(Err(CommandError::InvalidStep { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let discovery = SelectDiscovery::new("select/set", ["eco", "boost"]).build();
+
+        assert_eq!(
+            SelectCommand::decode(&discovery, "select/set", b"boost")
+                .unwrap()
+                .unwrap(),
+            SelectCommand("boost".into())
+        );
+        assert!(matches!(
+            SelectCommand::decode(&discovery, "select/set", b"turbo"),
+            Some(Err(CommandError::UnknownOption(option))) if option == "turbo"
+        ));
+    }
+
+    #[test]
+    fn test_text() {
+        let discovery = TextDiscovery::new("text/set")
+            .length(1, 8)
+            .pattern("[a-z]+$")
+            .build();
+
+        assert_eq!(
+            TextCommand::decode(&discovery, "text/set", b"hello")
+                .unwrap()
+                .unwrap(),
+            TextCommand("hello".into())
+        );
+        assert!(matches!(
+            TextCommand::decode(&discovery, "text/set", b"hello world"),
+            Some(Err(CommandError::InvalidLength { .. }))
+        ));
+        assert!(matches!(
+            TextCommand::decode(&discovery, "text/set", b"Hello"),
+            Some(Err(CommandError::PatternMismatch { .. }))
+        ));
+
+        // the pattern is serialized as a plain string, and compiled again after decoding
+        let value = serde_json::to_value(&discovery).unwrap();
+        assert_eq!(value["pattern"], "[a-z]+$");
+        let decoded: Discovery = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded.pattern, discovery.pattern);
+        assert!(matches!(
+            TextCommand::decode(&decoded, "text/set", b"Hello"),
+            Some(Err(CommandError::PatternMismatch { .. }))
+        ));
+
+        let discovery = TextDiscovery::new("text/set").pattern("[a-z").build();
+        assert!(matches!(
+            TextCommand::decode(&discovery, "text/set", b"hello"),
+            Some(Err(CommandError::InvalidPattern(_)))
+        ));
+    }
+
+    #[test]
+    fn test_mode() {
+        let mode: InputMode = serde_json::from_str(r#""password""#).unwrap();
+        assert_eq!(mode, InputMode::Text(TextMode::Password));
+        let mode: InputMode = serde_json::from_str(r#""slider""#).unwrap();
+        assert_eq!(mode, InputMode::Number(NumberMode::Slider));
+    }
+}
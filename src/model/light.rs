@@ -0,0 +1,243 @@
+use crate::model::{is_topic, Command, CommandError, Discovery};
+
+// also see: https://www.home-assistant.io/integrations/light.mqtt/#json-schema
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LightSchema {
+    Basic,
+    Json,
+    Template,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    Onoff,
+    Brightness,
+    ColorTemp,
+    Hs,
+    Xy,
+    Rgb,
+    Rgbw,
+    Rgbww,
+    White,
+}
+
+/// Builder for a light using the JSON schema.
+///
+/// Lights using the JSON schema always support transitions and flashing. Commands are
+/// received on the command topic and can be parsed using [`LightCommand::from_json`].
+#[derive(Clone, Debug)]
+pub struct LightDiscovery {
+    discovery: Discovery,
+}
+
+impl LightDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                schema: Some(LightSchema::Json),
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Set the topic the JSON state is published on.
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Enable brightness, using a scale from `1` to `scale`.
+    pub fn brightness(mut self, scale: u32) -> Self {
+        self.discovery.brightness = Some(true);
+        self.discovery.brightness_scale = Some(scale);
+        self
+    }
+
+    pub fn color_modes(mut self, modes: impl IntoIterator<Item = ColorMode>) -> Self {
+        self.discovery.supported_color_modes = modes.into_iter().collect();
+        self
+    }
+
+    /// Set the range of the color temperature, in kelvin.
+    pub fn color_temp_kelvin(mut self, min: u32, max: u32) -> Self {
+        self.discovery.color_temp_kelvin = Some(true);
+        self.discovery.min_kelvin = Some(min);
+        self.discovery.max_kelvin = Some(max);
+        self
+    }
+
+    /// Set the range of the color temperature, in mireds.
+    pub fn color_temp_mireds(mut self, min: u32, max: u32) -> Self {
+        self.discovery.color_temp_kelvin = None;
+        self.discovery.min_mireds = Some(min);
+        self.discovery.max_mireds = Some(max);
+        self
+    }
+
+    /// Enable effects, using the provided list.
+    pub fn effects<I>(mut self, effects: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.discovery.effect = Some(true);
+        self.discovery.effect_list = effects.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the duration of short and long flashes, in seconds.
+    pub fn flash_times(mut self, short: u32, long: u32) -> Self {
+        self.discovery.flash_time_short = Some(short);
+        self.discovery.flash_time_long = Some(long);
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<LightDiscovery> for Discovery {
+    fn from(value: LightDiscovery) -> Self {
+        value.build()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PowerState {
+    On,
+    Off,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flash {
+    Short,
+    Long,
+}
+
+/// The color of a JSON light command.
+///
+/// The variant is detected by the fields present in the payload.
+#[derive(Copy, Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum LightColor {
+    Rgbww { r: u8, g: u8, b: u8, c: u8, w: u8 },
+    Rgbw { r: u8, g: u8, b: u8, w: u8 },
+    Rgb { r: u8, g: u8, b: u8 },
+    Hs { h: f64, s: f64 },
+    Xy { x: f64, y: f64 },
+}
+
+/// A command of a JSON light.
+///
+/// The same structure is used to report the state of the light.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LightCommand {
+    pub state: PowerState,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_mode: Option<ColorMode>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<LightColor>,
+
+    /// The color temperature, in kelvin or mireds, depending on `color_temp_kelvin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_temp: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub white: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flash: Option<Flash>,
+
+    /// The transition time, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<f64>,
+}
+
+impl LightCommand {
+    pub fn from_json(payload: &[u8]) -> Result<Self, CommandError> {
+        Ok(serde_json::from_slice(payload)?)
+    }
+}
+
+impl Command for LightCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        is_topic(&discovery.command_topic, topic).then(|| Self::from_json(payload))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_discovery() {
+        let discovery = LightDiscovery::new("light/command")
+            .state_topic("light/state")
+            .brightness(100)
+            .color_modes([ColorMode::Rgb, ColorMode::ColorTemp])
+            .effects(["rainbow"])
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(discovery).unwrap(),
+            json!({
+                "name": null,
+                "device_class": null,
+                "schema": "json",
+                "command_topic": "light/command",
+                "state_topic": "light/state",
+                "brightness": true,
+                "brightness_scale": 100,
+                "supported_color_modes": ["rgb", "color_temp"],
+                "effect": true,
+                "effect_list": ["rainbow"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_command() {
+        let command = LightCommand::from_json(
+            br#"{"state":"ON","brightness":255,"color":{"r":255,"g":180,"b":200},"transition":2}"#,
+        )
+        .unwrap();
+
+        assert_eq!(command.state, PowerState::On);
+        assert_eq!(command.brightness, Some(255));
+        assert_eq!(
+            command.color,
+            Some(LightColor::Rgb {
+                r: 255,
+                g: 180,
+                b: 200
+            })
+        );
+        assert_eq!(command.transition, Some(2.0));
+
+        let command = LightCommand::from_json(br#"{"state":"OFF","flash":"short"}"#).unwrap();
+        assert_eq!(command.state, PowerState::Off);
+        assert_eq!(command.flash, Some(Flash::Short));
+    }
+}
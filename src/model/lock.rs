@@ -0,0 +1,191 @@
+use crate::model::{is_configured_payload, is_payload, is_topic, Command, CommandError, Discovery};
+
+// also see: https://www.home-assistant.io/integrations/lock.mqtt/
+
+/// Builder for a lock.
+///
+/// Commands received on the command topic can be decoded into a [`LockCommand`].
+#[derive(Clone, Debug)]
+pub struct LockDiscovery {
+    discovery: Discovery,
+}
+
+impl LockDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Set the topic the [`LockState`] is published on.
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Override the default command payloads (`LOCK`, `UNLOCK`).
+    pub fn payloads(mut self, lock: impl Into<String>, unlock: impl Into<String>) -> Self {
+        self.discovery.payload_lock = Some(lock.into());
+        self.discovery.payload_unlock = Some(unlock.into());
+        self
+    }
+
+    /// Enable opening the lock (e.g. unlatching a door), using the provided payload.
+    pub fn payload_open(mut self, open: impl Into<String>) -> Self {
+        self.discovery.payload_open = Some(open.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<LockDiscovery> for Discovery {
+    fn from(value: LockDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// The state of a lock.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockState {
+    Locked,
+    Locking,
+    Unlocked,
+    Unlocking,
+    Jammed,
+    Open,
+    Opening,
+}
+
+impl LockState {
+    /// The payload for this state, as configured by the discovery information.
+    pub fn payload<'a>(&self, discovery: &'a Discovery) -> &'a str {
+        let (configured, default) = match self {
+            Self::Locked => (&discovery.state_locked, "LOCKED"),
+            Self::Locking => (&discovery.state_locking, "LOCKING"),
+            Self::Unlocked => (&discovery.state_unlocked, "UNLOCKED"),
+            Self::Unlocking => (&discovery.state_unlocking, "UNLOCKING"),
+            Self::Jammed => (&discovery.state_jammed, "JAMMED"),
+            Self::Open => (&discovery.state_open, "OPEN"),
+            Self::Opening => (&discovery.state_opening, "OPENING"),
+        };
+        configured.as_deref().unwrap_or(default)
+    }
+}
+
+/// A command for a lock.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockCommand {
+    Lock,
+    Unlock,
+    /// Only available if `payload_open` is configured.
+    Open,
+}
+
+impl Command for LockCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(if is_payload(payload, &discovery.payload_lock, "LOCK") {
+            Ok(Self::Lock)
+        } else if is_payload(payload, &discovery.payload_unlock, "UNLOCK") {
+            Ok(Self::Unlock)
+        } else if is_configured_payload(payload, &discovery.payload_open) {
+            Ok(Self::Open)
+        } else {
+            Err(CommandError::invalid_payload(payload))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_discovery() {
+        let discovery = LockDiscovery::new("lock/set")
+            .state_topic("lock/state")
+            .payload_open("UNLATCH")
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&discovery).unwrap(),
+            json!({
+                "name": null,
+                "device_class": null,
+                "command_topic": "lock/set",
+                "state_topic": "lock/state",
+                "payload_open": "UNLATCH",
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let discovery = LockDiscovery::new("lock/set").build();
+        assert_eq!(
+            LockCommand::decode(&discovery, "lock/set", b"LOCK")
+                .unwrap()
+                .unwrap(),
+            LockCommand::Lock
+        );
+        assert_eq!(
+            LockCommand::decode(&discovery, "lock/set", b"UNLOCK")
+                .unwrap()
+                .unwrap(),
+            LockCommand::Unlock
+        );
+        // opening requires a configured payload
+        assert!(matches!(
+            LockCommand::decode(&discovery, "lock/set", b"OPEN"),
+            Some(Err(CommandError::InvalidPayload(payload))) if payload == "OPEN"
+        ));
+        assert!(LockCommand::decode(&discovery, "lock/other", b"LOCK").is_none());
+
+        let discovery = LockDiscovery::new("lock/set")
+            .payloads("close", "release")
+            .payload_open("UNLATCH")
+            .build();
+        assert_eq!(
+            LockCommand::decode(&discovery, "lock/set", b"release")
+                .unwrap()
+                .unwrap(),
+            LockCommand::Unlock
+        );
+        assert_eq!(
+            LockCommand::decode(&discovery, "lock/set", b"UNLATCH")
+                .unwrap()
+                .unwrap(),
+            LockCommand::Open
+        );
+        assert!(LockCommand::decode(&discovery, "lock/set", b"LOCK")
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_state() {
+        let discovery = LockDiscovery::new("lock/set").build();
+        assert_eq!(LockState::Locked.payload(&discovery), "LOCKED");
+        assert_eq!(LockState::Jammed.payload(&discovery), "JAMMED");
+
+        let discovery = Discovery {
+            state_open: Some("unlatched".into()),
+            ..discovery
+        };
+        assert_eq!(LockState::Open.payload(&discovery), "unlatched");
+    }
+}
@@ -1,33 +1,46 @@
+mod abbreviation;
+mod climate;
+mod command;
 mod component;
+mod cover;
+mod device;
 mod device_class;
+mod device_discovery;
 mod discovery;
+mod event;
+mod fan;
 mod id;
+mod input;
+mod light;
+mod lock;
+mod origin;
+mod sensor;
+mod state;
+mod switch;
+mod topic;
+mod unit;
+mod validation;
+mod valve;
 
+pub use abbreviation::*;
+pub use climate::*;
+pub use command::*;
 pub use component::*;
+pub use cover::*;
+pub use device::*;
 pub use device_class::*;
+pub use device_discovery::*;
 pub use discovery::*;
+pub use event::*;
+pub use fan::*;
 pub use id::*;
-
-#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
-pub struct Device {
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub identifiers: Vec<String>,
-
-    #[serde(default)]
-    pub name: Option<String>,
-
-    #[serde(rename = "~")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub base_topic: Option<String>,
-
-    /// Software version of the application that supplies the discovered MQTT item.
-    #[serde(alias = "sw")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub sw_version: Option<String>,
-
-    /// Support URL of the application that supplies the discovered MQTT item.
-    #[serde(alias = "url")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub support_url: Option<String>,
-}
+pub use input::*;
+pub use light::*;
+pub use lock::*;
+pub use origin::*;
+pub use sensor::*;
+pub use state::*;
+pub use switch::*;
+pub use unit::*;
+pub use validation::*;
+pub use valve::*;
@@ -0,0 +1,39 @@
+// also see: https://www.home-assistant.io/integrations/mqtt/#adding-information-about-the-origin-of-a-discovery-message
+
+/// The application which publishes a discovery message.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Origin {
+    /// The name of the application.
+    pub name: String,
+
+    /// Software version of the application.
+    #[serde(alias = "sw")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sw_version: Option<String>,
+
+    /// Support URL of the application.
+    #[serde(alias = "url")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub support_url: Option<String>,
+}
+
+impl Origin {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            sw_version: None,
+            support_url: None,
+        }
+    }
+
+    pub fn sw_version(mut self, sw_version: impl Into<String>) -> Self {
+        self.sw_version = Some(sw_version.into());
+        self
+    }
+
+    pub fn support_url(mut self, support_url: impl Into<String>) -> Self {
+        self.support_url = Some(support_url.into());
+        self
+    }
+}
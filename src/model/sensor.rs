@@ -0,0 +1,102 @@
+use crate::model::{BinarySensorClass, Discovery, SensorClass, StateClass};
+
+// also see:
+//  * https://www.home-assistant.io/integrations/sensor.mqtt/
+//  * https://www.home-assistant.io/integrations/binary_sensor.mqtt/
+
+/// Builder for a sensor.
+#[derive(Clone, Debug)]
+pub struct SensorDiscovery {
+    discovery: Discovery,
+}
+
+impl SensorDiscovery {
+    pub fn new(state_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                state_topic: Some(state_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: SensorClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    pub fn state_class(mut self, state_class: StateClass) -> Self {
+        self.discovery.state_class = Some(state_class);
+        self
+    }
+
+    pub fn unit_of_measurement(mut self, unit: impl Into<String>) -> Self {
+        self.discovery.unit_of_measurement = Some(unit.into());
+        self
+    }
+
+    /// Set the number of decimals, also used when publishing a [`crate::model::StateValue`].
+    pub fn suggested_display_precision(mut self, precision: u32) -> Self {
+        self.discovery.suggested_display_precision = Some(precision);
+        self
+    }
+
+    pub fn value_template(mut self, value_template: impl Into<String>) -> Self {
+        self.discovery.value_template = Some(value_template.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<SensorDiscovery> for Discovery {
+    fn from(value: SensorDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// Builder for a binary sensor.
+#[derive(Clone, Debug)]
+pub struct BinarySensorDiscovery {
+    discovery: Discovery,
+}
+
+impl BinarySensorDiscovery {
+    pub fn new(state_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                state_topic: Some(state_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: BinarySensorClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    /// Override the default state payloads (`ON`, `OFF`).
+    pub fn payloads(mut self, on: impl Into<String>, off: impl Into<String>) -> Self {
+        self.discovery.payload_on = Some(on.into());
+        self.discovery.payload_off = Some(off.into());
+        self
+    }
+
+    pub fn value_template(mut self, value_template: impl Into<String>) -> Self {
+        self.discovery.value_template = Some(value_template.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<BinarySensorDiscovery> for Discovery {
+    fn from(value: BinarySensorDiscovery) -> Self {
+        value.build()
+    }
+}
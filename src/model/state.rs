@@ -0,0 +1,156 @@
+use crate::model::Discovery;
+use std::time::SystemTime;
+
+/// An error encoding a [`StateValue`].
+#[derive(Debug, thiserror::Error)]
+pub enum StateError {
+    #[error("unknown option: {0:?}")]
+    UnknownOption(String),
+    #[error("invalid JSON value: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A typed state, encoded as declared by the discovery information of the entity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateValue {
+    /// On or off, encoded using `state_on`/`state_off`, or `payload_on`/`payload_off`.
+    Bool(bool),
+    /// A number, using the `suggested_display_precision` if present.
+    Number(f64),
+    /// A point in time, encoded as ISO 8601 timestamp in UTC.
+    Timestamp(SystemTime),
+    /// One of the options of an `enum` sensor or select.
+    Option(String),
+    /// A JSON value, like an object which gets processed by a value template.
+    Json(serde_json::Value),
+}
+
+impl StateValue {
+    /// Encode the state, as declared by the discovery information.
+    pub fn encode(&self, discovery: &Discovery) -> Result<Vec<u8>, StateError> {
+        Ok(match self {
+            Self::Bool(true) => Self::payload(&discovery.state_on, &discovery.payload_on, "ON"),
+            Self::Bool(false) => Self::payload(&discovery.state_off, &discovery.payload_off, "OFF"),
+            Self::Number(value) => match discovery.suggested_display_precision {
+                Some(precision) => format!("{value:.*}", precision as usize).into_bytes(),
+                None => value.to_string().into_bytes(),
+            },
+            Self::Timestamp(value) => humantime::format_rfc3339(*value).to_string().into_bytes(),
+            Self::Option(value) => {
+                if !discovery.options.is_empty() && !discovery.options.contains(value) {
+                    return Err(StateError::UnknownOption(value.clone()));
+                }
+                value.clone().into_bytes()
+            }
+            Self::Json(value) => serde_json::to_vec(value)?,
+        })
+    }
+
+    fn payload(state: &Option<String>, payload: &Option<String>, default: &str) -> Vec<u8> {
+        state
+            .as_deref()
+            .or(payload.as_deref())
+            .unwrap_or(default)
+            .as_bytes()
+            .to_vec()
+    }
+}
+
+impl From<bool> for StateValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for StateValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for StateValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<SystemTime> for StateValue {
+    fn from(value: SystemTime) -> Self {
+        Self::Timestamp(value)
+    }
+}
+
+impl From<serde_json::Value> for StateValue {
+    fn from(value: serde_json::Value) -> Self {
+        Self::Json(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{BinarySensorDiscovery, SelectDiscovery, SensorDiscovery, SwitchDiscovery};
+    use serde_json::json;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn encode(value: impl Into<StateValue>, discovery: &Discovery) -> String {
+        String::from_utf8(value.into().encode(discovery).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_bool() {
+        let discovery = SwitchDiscovery::new("switch/set").build();
+        assert_eq!(encode(true, &discovery), "ON");
+
+        let discovery = BinarySensorDiscovery::new("sensor/state")
+            .payloads("1", "0")
+            .build();
+        assert_eq!(encode(false, &discovery), "0");
+
+        let discovery = SwitchDiscovery::new("switch/set")
+            .payloads("on", "off")
+            .state_payloads("enabled", "disabled")
+            .build();
+        assert_eq!(encode(true, &discovery), "enabled");
+    }
+
+    #[test]
+    fn test_number() {
+        let discovery = SensorDiscovery::new("sensor/state").build();
+        assert_eq!(encode(21.25, &discovery), "21.25");
+        assert_eq!(encode(21, &discovery), "21");
+
+        let discovery = SensorDiscovery::new("sensor/state")
+            .suggested_display_precision(1)
+            .build();
+        assert_eq!(encode(21.25, &discovery), "21.2");
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let discovery = SensorDiscovery::new("sensor/state").build();
+        assert_eq!(
+            encode(UNIX_EPOCH + Duration::from_secs(1_700_000_000), &discovery),
+            "2023-11-14T22:13:20Z"
+        );
+    }
+
+    #[test]
+    fn test_option() {
+        let discovery = SelectDiscovery::new("select/set", ["eco", "boost"]).build();
+        assert_eq!(encode(StateValue::Option("eco".into()), &discovery), "eco");
+        assert!(matches!(
+            StateValue::Option("turbo".into()).encode(&discovery),
+            Err(StateError::UnknownOption(option)) if option == "turbo"
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let discovery = SensorDiscovery::new("sensor/state").build();
+        assert_eq!(
+            encode(json!({"temperature": 21.5}), &discovery),
+            r#"{"temperature":21.5}"#
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::model::{
+    is_payload, is_topic, ButtonClass, Command, CommandError, Discovery, SwitchClass,
+};
+
+// also see:
+//  * https://www.home-assistant.io/integrations/switch.mqtt/
+//  * https://www.home-assistant.io/integrations/button.mqtt/
+
+/// Builder for a switch.
+///
+/// Commands can be decoded into a [`SwitchCommand`].
+#[derive(Clone, Debug)]
+pub struct SwitchDiscovery {
+    discovery: Discovery,
+}
+
+impl SwitchDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    pub fn device_class(mut self, device_class: SwitchClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    /// Override the default command payloads (`ON`, `OFF`).
+    pub fn payloads(mut self, on: impl Into<String>, off: impl Into<String>) -> Self {
+        self.discovery.payload_on = Some(on.into());
+        self.discovery.payload_off = Some(off.into());
+        self
+    }
+
+    /// Override the state payloads, which default to the command payloads.
+    pub fn state_payloads(mut self, on: impl Into<String>, off: impl Into<String>) -> Self {
+        self.discovery.state_on = Some(on.into());
+        self.discovery.state_off = Some(off.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<SwitchDiscovery> for Discovery {
+    fn from(value: SwitchDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// Builder for a button.
+///
+/// Commands can be decoded into a [`ButtonCommand`].
+#[derive(Clone, Debug)]
+pub struct ButtonDiscovery {
+    discovery: Discovery,
+}
+
+impl ButtonDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: ButtonClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    /// Override the default command payload (`PRESS`).
+    pub fn payload_press(mut self, press: impl Into<String>) -> Self {
+        self.discovery.payload_press = Some(press.into());
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<ButtonDiscovery> for Discovery {
+    fn from(value: ButtonDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// A command for a switch.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwitchCommand {
+    TurnOn,
+    TurnOff,
+}
+
+impl Command for SwitchCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(if is_payload(payload, &discovery.payload_on, "ON") {
+            Ok(Self::TurnOn)
+        } else if is_payload(payload, &discovery.payload_off, "OFF") {
+            Ok(Self::TurnOff)
+        } else {
+            Err(CommandError::invalid_payload(payload))
+        })
+    }
+}
+
+/// A button press.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ButtonCommand;
+
+impl Command for ButtonCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(if is_payload(payload, &discovery.payload_press, "PRESS") {
+            Ok(Self)
+        } else {
+            Err(CommandError::invalid_payload(payload))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let switch = SwitchDiscovery::new("switch/set")
+            .payloads("1", "0")
+            .build();
+        assert_eq!(
+            SwitchCommand::decode(&switch, "switch/set", b"1")
+                .unwrap()
+                .unwrap(),
+            SwitchCommand::TurnOn
+        );
+        assert!(SwitchCommand::decode(&switch, "switch/set", b"ON")
+            .unwrap()
+            .is_err());
+        assert!(SwitchCommand::decode(&switch, "switch/state", b"1").is_none());
+
+        let button = ButtonDiscovery::new("button/set").build();
+        assert_eq!(
+            ButtonCommand::decode(&button, "button/set", b"PRESS")
+                .unwrap()
+                .unwrap(),
+            ButtonCommand
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::model::Discovery;
+
+// also see: https://www.home-assistant.io/integrations/mqtt/#using-abbreviations-and-base-topic
+
+const BASE: char = '~';
+
+impl Discovery {
+    /// All topics of the discovery message, including the availability topics.
+    pub(crate) fn topics_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        [
+            &mut self.command_topic,
+            &mut self.state_topic,
+            &mut self.json_attributes_topic,
+            // climate
+            &mut self.mode_command_topic,
+            &mut self.mode_state_topic,
+            &mut self.temperature_command_topic,
+            &mut self.temperature_state_topic,
+            &mut self.temperature_low_command_topic,
+            &mut self.temperature_low_state_topic,
+            &mut self.temperature_high_command_topic,
+            &mut self.temperature_high_state_topic,
+            &mut self.current_temperature_topic,
+            &mut self.action_topic,
+            &mut self.preset_mode_command_topic,
+            &mut self.preset_mode_state_topic,
+            &mut self.fan_mode_command_topic,
+            &mut self.fan_mode_state_topic,
+            // cover
+            &mut self.position_topic,
+            &mut self.set_position_topic,
+            &mut self.tilt_command_topic,
+            &mut self.tilt_status_topic,
+            // fan
+            &mut self.percentage_command_topic,
+            &mut self.percentage_state_topic,
+            &mut self.oscillation_command_topic,
+            &mut self.oscillation_state_topic,
+            &mut self.direction_command_topic,
+            &mut self.direction_state_topic,
+            // device trigger
+            &mut self.topic,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.availability.iter_mut().map(|a| &mut a.topic))
+    }
+
+    /// All topics on which Home Assistant sends commands.
+    pub fn command_topics(&self) -> impl Iterator<Item = &str> {
+        [
+            &self.command_topic,
+            &self.mode_command_topic,
+            &self.temperature_command_topic,
+            &self.temperature_low_command_topic,
+            &self.temperature_high_command_topic,
+            &self.preset_mode_command_topic,
+            &self.fan_mode_command_topic,
+            &self.set_position_topic,
+            &self.tilt_command_topic,
+            &self.percentage_command_topic,
+            &self.oscillation_command_topic,
+            &self.direction_command_topic,
+        ]
+        .into_iter()
+        .filter_map(Option::as_deref)
+    }
+
+    /// Replace the base topic in all topics with `~`.
+    ///
+    /// The base topic is the one of the discovery message, or of its device. If neither is set,
+    /// the longest common prefix of all topics is used, if there is one.
+    pub fn compress_topics(&mut self) {
+        let Some(base) = self
+            .base_topic
+            .clone()
+            .or_else(|| self.device.as_ref().and_then(|d| d.base_topic.clone()))
+            .or_else(|| common_prefix(self.topics_mut().map(|t| &*t)))
+        else {
+            return;
+        };
+
+        let mut compressed = false;
+        for topic in self.topics_mut() {
+            if let Some(rest) = topic.strip_prefix(&base) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    *topic = format!("{BASE}{rest}");
+                    compressed = true;
+                }
+            }
+        }
+
+        if compressed {
+            self.base_topic = Some(base);
+        }
+    }
+
+    /// Replace a `~` at the start or end of all topics with the base topic, and clear the base
+    /// topic.
+    ///
+    /// This turns a discovery message, as received from the broker, into one with absolute
+    /// topics.
+    pub fn expand_topics(&mut self) {
+        let Some(base) = self
+            .base_topic
+            .take()
+            .or_else(|| self.device.as_ref().and_then(|d| d.base_topic.clone()))
+        else {
+            return;
+        };
+
+        for topic in self.topics_mut() {
+            if let Some(rest) = topic.strip_prefix(BASE) {
+                *topic = format!("{base}{rest}");
+            } else if let Some(rest) = topic.strip_suffix(BASE) {
+                *topic = format!("{rest}{base}");
+            }
+        }
+    }
+}
+
+/// The longest common prefix, in full topic levels, of at least two topics.
+fn common_prefix<'a>(topics: impl Iterator<Item = &'a String>) -> Option<String> {
+    let mut count = 0;
+    let mut prefix: Option<Vec<&str>> = None;
+
+    for topic in topics {
+        count += 1;
+        // keep at least the last level
+        let levels = topic.rsplit_once('/')?.0.split('/').collect::<Vec<_>>();
+        prefix = Some(match prefix {
+            None => levels,
+            Some(prefix) => prefix
+                .into_iter()
+                .zip(levels)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+
+    prefix
+        .filter(|prefix| count > 1 && !prefix.is_empty())
+        .map(|prefix| prefix.join("/"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{Availability, Device, LightDiscovery};
+
+    fn light() -> Discovery {
+        Discovery {
+            availability: vec![Availability::new("agent/status")],
+            ..LightDiscovery::new("agent/light-1/set")
+                .state_topic("agent/light-1/state")
+                .build()
+        }
+    }
+
+    #[test]
+    fn test_compress_common_prefix() {
+        let mut discovery = Discovery {
+            availability: vec![],
+            ..light()
+        };
+        discovery.compress_topics();
+
+        assert_eq!(discovery.base_topic.as_deref(), Some("agent/light-1"));
+        assert_eq!(discovery.command_topic.as_deref(), Some("~/set"));
+        assert_eq!(discovery.state_topic.as_deref(), Some("~/state"));
+
+        discovery.expand_topics();
+        assert_eq!(
+            discovery,
+            Discovery {
+                availability: vec![],
+                ..light()
+            }
+        );
+    }
+
+    #[test]
+    fn test_compress_device() {
+        let mut discovery = Discovery {
+            device: Some(Device {
+                base_topic: Some("agent".into()),
+                ..Device::new("device-1")
+            }),
+            ..light()
+        };
+        discovery.compress_topics();
+
+        assert_eq!(discovery.base_topic.as_deref(), Some("agent"));
+        assert_eq!(discovery.command_topic.as_deref(), Some("~/light-1/set"));
+        assert_eq!(discovery.availability[0].topic, "~/status");
+    }
+
+    #[test]
+    fn test_no_common_prefix() {
+        let mut discovery = Discovery {
+            state_topic: Some("other/light-1/state".into()),
+            ..light()
+        };
+        discovery.compress_topics();
+
+        assert_eq!(discovery.base_topic, None);
+        assert_eq!(
+            discovery.command_topic.as_deref(),
+            Some("agent/light-1/set")
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut discovery: Discovery = serde_json::from_value(serde_json::json!({
+            "~": "agent/light-1",
+            "cmd_t": "~/set",
+            "stat_t": "state/~",
+            "json_attr_t": "~/attributes",
+        }))
+        .unwrap();
+        discovery.expand_topics();
+
+        assert_eq!(discovery.base_topic, None);
+        assert_eq!(
+            discovery.command_topic.as_deref(),
+            Some("agent/light-1/set")
+        );
+        assert_eq!(
+            discovery.state_topic.as_deref(),
+            Some("state/agent/light-1")
+        );
+        assert_eq!(
+            discovery.json_attributes_topic.as_deref(),
+            Some("agent/light-1/attributes")
+        );
+    }
+}
@@ -0,0 +1,744 @@
+use crate::model::{SensorClass, StateClass};
+
+// for values see:
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/const.py
+//  * https://github.com/home-assistant/core/blob/dev/homeassistant/components/sensor/const.py
+
+/// A unit of measurement.
+///
+/// Can be converted into the string used by Home Assistant, so it can be used with
+/// [`crate::model::SensorDiscovery::unit_of_measurement`] and
+/// [`crate::model::NumberDiscovery::unit_of_measurement`].
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, strum::AsRefStr, strum::EnumString, strum::VariantNames,
+)]
+pub enum Unit {
+    // temperature
+    #[strum(to_string = "°C")]
+    Celsius,
+    #[strum(to_string = "°F")]
+    Fahrenheit,
+    #[strum(to_string = "K")]
+    Kelvin,
+
+    // ratio
+    #[strum(to_string = "%")]
+    Percentage,
+
+    // power
+    #[strum(to_string = "mW")]
+    Milliwatt,
+    #[strum(to_string = "W")]
+    Watt,
+    #[strum(to_string = "kW")]
+    Kilowatt,
+    #[strum(to_string = "MW")]
+    Megawatt,
+    #[strum(to_string = "GW")]
+    Gigawatt,
+    #[strum(to_string = "TW")]
+    Terawatt,
+    #[strum(to_string = "BTU/h")]
+    BtuPerHour,
+    #[strum(to_string = "VA")]
+    VoltAmpere,
+    #[strum(to_string = "kVA")]
+    KiloVoltAmpere,
+    #[strum(to_string = "var")]
+    VoltAmpereReactive,
+    #[strum(to_string = "kvar")]
+    KiloVoltAmpereReactive,
+
+    // energy
+    #[strum(to_string = "J")]
+    Joule,
+    #[strum(to_string = "kJ")]
+    Kilojoule,
+    #[strum(to_string = "MJ")]
+    Megajoule,
+    #[strum(to_string = "GJ")]
+    Gigajoule,
+    #[strum(to_string = "mWh")]
+    MilliwattHour,
+    #[strum(to_string = "Wh")]
+    WattHour,
+    #[strum(to_string = "kWh")]
+    KilowattHour,
+    #[strum(to_string = "MWh")]
+    MegawattHour,
+    #[strum(to_string = "GWh")]
+    GigawattHour,
+    #[strum(to_string = "TWh")]
+    TerawattHour,
+    #[strum(to_string = "cal")]
+    Calorie,
+    #[strum(to_string = "kcal")]
+    Kilocalorie,
+    #[strum(to_string = "Mcal")]
+    Megacalorie,
+    #[strum(to_string = "Gcal")]
+    Gigacalorie,
+    #[strum(to_string = "varh")]
+    VoltAmpereReactiveHour,
+    #[strum(to_string = "kvarh")]
+    KiloVoltAmpereReactiveHour,
+
+    // energy distance
+    #[strum(to_string = "kWh/100km")]
+    KilowattHourPer100Kilometers,
+    #[strum(to_string = "Wh/km")]
+    WattHourPerKilometer,
+    #[strum(to_string = "mi/kWh")]
+    MilesPerKilowattHour,
+    #[strum(to_string = "km/kWh")]
+    KilometersPerKilowattHour,
+
+    // electric potential
+    #[strum(to_string = "μV", serialize = "µV")]
+    Microvolt,
+    #[strum(to_string = "mV")]
+    Millivolt,
+    #[strum(to_string = "V")]
+    Volt,
+    #[strum(to_string = "kV")]
+    Kilovolt,
+    #[strum(to_string = "MV")]
+    Megavolt,
+
+    // electric current
+    #[strum(to_string = "mA")]
+    Milliampere,
+    #[strum(to_string = "A")]
+    Ampere,
+
+    // conductivity
+    #[strum(to_string = "S/cm")]
+    SiemensPerCentimeter,
+    #[strum(to_string = "μS/cm", serialize = "µS/cm")]
+    MicrosiemensPerCentimeter,
+    #[strum(to_string = "mS/cm")]
+    MillisiemensPerCentimeter,
+
+    // frequency
+    #[strum(to_string = "Hz")]
+    Hertz,
+    #[strum(to_string = "kHz")]
+    Kilohertz,
+    #[strum(to_string = "MHz")]
+    Megahertz,
+    #[strum(to_string = "GHz")]
+    Gigahertz,
+
+    // pressure
+    #[strum(to_string = "Pa")]
+    Pascal,
+    #[strum(to_string = "hPa")]
+    Hectopascal,
+    #[strum(to_string = "kPa")]
+    Kilopascal,
+    #[strum(to_string = "bar")]
+    Bar,
+    #[strum(to_string = "cbar")]
+    Centibar,
+    #[strum(to_string = "mbar")]
+    Millibar,
+    #[strum(to_string = "mmHg")]
+    MillimeterOfMercury,
+    #[strum(to_string = "inHg")]
+    InchOfMercury,
+    #[strum(to_string = "psi")]
+    Psi,
+
+    // sound pressure, signal strength
+    #[strum(to_string = "dB")]
+    Decibel,
+    #[strum(to_string = "dBA")]
+    WeightedDecibelA,
+    #[strum(to_string = "dBm")]
+    DecibelMilliwatt,
+
+    // volume
+    #[strum(to_string = "L")]
+    Liter,
+    #[strum(to_string = "mL")]
+    Milliliter,
+    #[strum(to_string = "m³")]
+    CubicMeter,
+    #[strum(to_string = "ft³")]
+    CubicFoot,
+    #[strum(to_string = "CCF")]
+    CentumCubicFoot,
+    #[strum(to_string = "MCF")]
+    MilleCubicFoot,
+    #[strum(to_string = "gal")]
+    Gallon,
+    #[strum(to_string = "fl. oz.")]
+    FluidOunce,
+
+    // volume flow rate
+    #[strum(to_string = "m³/h")]
+    CubicMeterPerHour,
+    #[strum(to_string = "m³/s")]
+    CubicMeterPerSecond,
+    #[strum(to_string = "ft³/min")]
+    CubicFootPerMinute,
+    #[strum(to_string = "L/h")]
+    LiterPerHour,
+    #[strum(to_string = "L/min")]
+    LiterPerMinute,
+    #[strum(to_string = "L/s")]
+    LiterPerSecond,
+    #[strum(to_string = "mL/s")]
+    MilliliterPerSecond,
+    #[strum(to_string = "gal/min")]
+    GallonPerMinute,
+    #[strum(to_string = "gal/d")]
+    GallonPerDay,
+
+    // area
+    #[strum(to_string = "m²")]
+    SquareMeter,
+    #[strum(to_string = "cm²")]
+    SquareCentimeter,
+    #[strum(to_string = "km²")]
+    SquareKilometer,
+    #[strum(to_string = "mm²")]
+    SquareMillimeter,
+    #[strum(to_string = "in²")]
+    SquareInch,
+    #[strum(to_string = "ft²")]
+    SquareFoot,
+    #[strum(to_string = "yd²")]
+    SquareYard,
+    #[strum(to_string = "mi²")]
+    SquareMile,
+    #[strum(to_string = "ac")]
+    Acre,
+    #[strum(to_string = "ha")]
+    Hectare,
+
+    // time
+    #[strum(to_string = "μs", serialize = "µs")]
+    Microsecond,
+    #[strum(to_string = "ms")]
+    Millisecond,
+    #[strum(to_string = "s")]
+    Second,
+    #[strum(to_string = "min")]
+    Minute,
+    #[strum(to_string = "h")]
+    Hour,
+    #[strum(to_string = "d")]
+    Day,
+    #[strum(to_string = "w")]
+    Week,
+
+    // length
+    #[strum(to_string = "mm")]
+    Millimeter,
+    #[strum(to_string = "cm")]
+    Centimeter,
+    #[strum(to_string = "m")]
+    Meter,
+    #[strum(to_string = "km")]
+    Kilometer,
+    #[strum(to_string = "in")]
+    Inch,
+    #[strum(to_string = "ft")]
+    Foot,
+    #[strum(to_string = "yd")]
+    Yard,
+    #[strum(to_string = "mi")]
+    Mile,
+    #[strum(to_string = "nmi")]
+    NauticalMile,
+
+    // speed, precipitation intensity
+    #[strum(to_string = "mm/d")]
+    MillimeterPerDay,
+    #[strum(to_string = "mm/h")]
+    MillimeterPerHour,
+    #[strum(to_string = "mm/s")]
+    MillimeterPerSecond,
+    #[strum(to_string = "in/d")]
+    InchPerDay,
+    #[strum(to_string = "in/h")]
+    InchPerHour,
+    #[strum(to_string = "in/s")]
+    InchPerSecond,
+    #[strum(to_string = "ft/s")]
+    FootPerSecond,
+    #[strum(to_string = "m/s")]
+    MeterPerSecond,
+    #[strum(to_string = "km/h")]
+    KilometerPerHour,
+    #[strum(to_string = "kn")]
+    Knot,
+    #[strum(to_string = "mph")]
+    MilePerHour,
+    #[strum(to_string = "Beaufort")]
+    Beaufort,
+
+    // mass
+    #[strum(to_string = "μg", serialize = "µg")]
+    Microgram,
+    #[strum(to_string = "mg")]
+    Milligram,
+    #[strum(to_string = "g")]
+    Gram,
+    #[strum(to_string = "kg")]
+    Kilogram,
+    #[strum(to_string = "oz")]
+    Ounce,
+    #[strum(to_string = "lb")]
+    Pound,
+    #[strum(to_string = "st")]
+    Stone,
+
+    // concentration
+    #[strum(to_string = "μg/m³", serialize = "µg/m³")]
+    MicrogramPerCubicMeter,
+    #[strum(to_string = "mg/m³")]
+    MilligramPerCubicMeter,
+    #[strum(to_string = "g/m³")]
+    GramPerCubicMeter,
+    #[strum(to_string = "μg/ft³", serialize = "µg/ft³")]
+    MicrogramPerCubicFoot,
+    #[strum(to_string = "p/m³")]
+    PartsPerCubicMeter,
+    #[strum(to_string = "ppm")]
+    PartsPerMillion,
+    #[strum(to_string = "ppb")]
+    PartsPerBillion,
+    #[strum(to_string = "mg/dL")]
+    MilligramPerDeciliter,
+    #[strum(to_string = "mmol/L")]
+    MillimolePerLiter,
+
+    // irradiance, illuminance
+    #[strum(to_string = "W/m²")]
+    WattPerSquareMeter,
+    #[strum(to_string = "BTU/(h⋅ft²)")]
+    BtuPerHourSquareFoot,
+    #[strum(to_string = "lx")]
+    Lux,
+
+    // angle
+    #[strum(to_string = "°")]
+    Degree,
+
+    // data rate
+    #[strum(to_string = "bit/s")]
+    BitPerSecond,
+    #[strum(to_string = "kbit/s")]
+    KilobitPerSecond,
+    #[strum(to_string = "Mbit/s")]
+    MegabitPerSecond,
+    #[strum(to_string = "Gbit/s")]
+    GigabitPerSecond,
+    #[strum(to_string = "B/s")]
+    BytePerSecond,
+    #[strum(to_string = "kB/s")]
+    KilobytePerSecond,
+    #[strum(to_string = "MB/s")]
+    MegabytePerSecond,
+    #[strum(to_string = "GB/s")]
+    GigabytePerSecond,
+    #[strum(to_string = "KiB/s")]
+    KibibytePerSecond,
+    #[strum(to_string = "MiB/s")]
+    MebibytePerSecond,
+    #[strum(to_string = "GiB/s")]
+    GibibytePerSecond,
+
+    // data size
+    #[strum(to_string = "bit")]
+    Bit,
+    #[strum(to_string = "kbit")]
+    Kilobit,
+    #[strum(to_string = "Mbit")]
+    Megabit,
+    #[strum(to_string = "Gbit")]
+    Gigabit,
+    #[strum(to_string = "B")]
+    Byte,
+    #[strum(to_string = "kB")]
+    Kilobyte,
+    #[strum(to_string = "MB")]
+    Megabyte,
+    #[strum(to_string = "GB")]
+    Gigabyte,
+    #[strum(to_string = "TB")]
+    Terabyte,
+    #[strum(to_string = "PB")]
+    Petabyte,
+    #[strum(to_string = "KiB")]
+    Kibibyte,
+    #[strum(to_string = "MiB")]
+    Mebibyte,
+    #[strum(to_string = "GiB")]
+    Gibibyte,
+    #[strum(to_string = "TiB")]
+    Tebibyte,
+    #[strum(to_string = "PiB")]
+    Pebibyte,
+}
+
+impl From<Unit> for String {
+    fn from(value: Unit) -> Self {
+        value.as_ref().to_string()
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+const TEMPERATURE: &[Unit] = &[Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin];
+const PERCENTAGE: &[Unit] = &[Unit::Percentage];
+const POWER: &[Unit] = &[
+    Unit::Milliwatt,
+    Unit::Watt,
+    Unit::Kilowatt,
+    Unit::Megawatt,
+    Unit::Gigawatt,
+    Unit::Terawatt,
+    Unit::BtuPerHour,
+];
+const ENERGY: &[Unit] = &[
+    Unit::Joule,
+    Unit::Kilojoule,
+    Unit::Megajoule,
+    Unit::Gigajoule,
+    Unit::MilliwattHour,
+    Unit::WattHour,
+    Unit::KilowattHour,
+    Unit::MegawattHour,
+    Unit::GigawattHour,
+    Unit::TerawattHour,
+    Unit::Calorie,
+    Unit::Kilocalorie,
+    Unit::Megacalorie,
+    Unit::Gigacalorie,
+];
+const PRESSURE: &[Unit] = &[
+    Unit::Pascal,
+    Unit::Hectopascal,
+    Unit::Kilopascal,
+    Unit::Bar,
+    Unit::Centibar,
+    Unit::Millibar,
+    Unit::MillimeterOfMercury,
+    Unit::InchOfMercury,
+    Unit::Psi,
+];
+const VOLUME: &[Unit] = &[
+    Unit::Liter,
+    Unit::Milliliter,
+    Unit::CubicMeter,
+    Unit::CubicFoot,
+    Unit::CentumCubicFoot,
+    Unit::MilleCubicFoot,
+    Unit::Gallon,
+    Unit::FluidOunce,
+];
+const GAS: &[Unit] = &[
+    Unit::Liter,
+    Unit::CubicMeter,
+    Unit::CubicFoot,
+    Unit::CentumCubicFoot,
+    Unit::MilleCubicFoot,
+];
+const WATER: &[Unit] = &[
+    Unit::Liter,
+    Unit::CubicMeter,
+    Unit::CubicFoot,
+    Unit::CentumCubicFoot,
+    Unit::MilleCubicFoot,
+    Unit::Gallon,
+];
+const VOLUME_FLOW_RATE: &[Unit] = &[
+    Unit::CubicMeterPerHour,
+    Unit::CubicMeterPerSecond,
+    Unit::CubicFootPerMinute,
+    Unit::LiterPerHour,
+    Unit::LiterPerMinute,
+    Unit::LiterPerSecond,
+    Unit::MilliliterPerSecond,
+    Unit::GallonPerMinute,
+    Unit::GallonPerDay,
+];
+const AREA: &[Unit] = &[
+    Unit::SquareMeter,
+    Unit::SquareCentimeter,
+    Unit::SquareKilometer,
+    Unit::SquareMillimeter,
+    Unit::SquareInch,
+    Unit::SquareFoot,
+    Unit::SquareYard,
+    Unit::SquareMile,
+    Unit::Acre,
+    Unit::Hectare,
+];
+const DURATION: &[Unit] = &[
+    Unit::Microsecond,
+    Unit::Millisecond,
+    Unit::Second,
+    Unit::Minute,
+    Unit::Hour,
+    Unit::Day,
+];
+const DISTANCE: &[Unit] = &[
+    Unit::Millimeter,
+    Unit::Centimeter,
+    Unit::Meter,
+    Unit::Kilometer,
+    Unit::Inch,
+    Unit::Foot,
+    Unit::Yard,
+    Unit::Mile,
+    Unit::NauticalMile,
+];
+const PRECIPITATION: &[Unit] = &[Unit::Millimeter, Unit::Centimeter, Unit::Inch];
+const PRECIPITATION_INTENSITY: &[Unit] = &[
+    Unit::MillimeterPerDay,
+    Unit::MillimeterPerHour,
+    Unit::InchPerDay,
+    Unit::InchPerHour,
+];
+const SPEED: &[Unit] = &[
+    Unit::MillimeterPerDay,
+    Unit::MillimeterPerHour,
+    Unit::MillimeterPerSecond,
+    Unit::InchPerDay,
+    Unit::InchPerHour,
+    Unit::InchPerSecond,
+    Unit::FootPerSecond,
+    Unit::MeterPerSecond,
+    Unit::KilometerPerHour,
+    Unit::Knot,
+    Unit::MilePerHour,
+    Unit::Beaufort,
+];
+const WIND_SPEED: &[Unit] = &[
+    Unit::FootPerSecond,
+    Unit::MeterPerSecond,
+    Unit::KilometerPerHour,
+    Unit::Knot,
+    Unit::MilePerHour,
+    Unit::Beaufort,
+];
+const WEIGHT: &[Unit] = &[
+    Unit::Microgram,
+    Unit::Milligram,
+    Unit::Gram,
+    Unit::Kilogram,
+    Unit::Ounce,
+    Unit::Pound,
+    Unit::Stone,
+];
+const DATA_RATE: &[Unit] = &[
+    Unit::BitPerSecond,
+    Unit::KilobitPerSecond,
+    Unit::MegabitPerSecond,
+    Unit::GigabitPerSecond,
+    Unit::BytePerSecond,
+    Unit::KilobytePerSecond,
+    Unit::MegabytePerSecond,
+    Unit::GigabytePerSecond,
+    Unit::KibibytePerSecond,
+    Unit::MebibytePerSecond,
+    Unit::GibibytePerSecond,
+];
+const DATA_SIZE: &[Unit] = &[
+    Unit::Bit,
+    Unit::Kilobit,
+    Unit::Megabit,
+    Unit::Gigabit,
+    Unit::Byte,
+    Unit::Kilobyte,
+    Unit::Megabyte,
+    Unit::Gigabyte,
+    Unit::Terabyte,
+    Unit::Petabyte,
+    Unit::Kibibyte,
+    Unit::Mebibyte,
+    Unit::Gibibyte,
+    Unit::Tebibyte,
+    Unit::Pebibyte,
+];
+const MICROGRAMS: &[Unit] = &[Unit::MicrogramPerCubicMeter];
+
+const MEASUREMENT: &[StateClass] = &[StateClass::Measurement];
+const TOTAL: &[StateClass] = &[StateClass::Total, StateClass::TotalIncreasing];
+const ANY: &[StateClass] = &[
+    StateClass::Measurement,
+    StateClass::Total,
+    StateClass::TotalIncreasing,
+];
+
+impl SensorClass {
+    /// The units a sensor of this class accepts.
+    ///
+    /// Returns `None` if any unit is accepted, like currencies for [`SensorClass::Monetary`].
+    /// An empty list means that no unit must be used.
+    pub fn units(&self) -> Option<&'static [Unit]> {
+        Some(match self {
+            Self::AbsoluteHumidity => &[Unit::GramPerCubicMeter, Unit::MilligramPerCubicMeter],
+            Self::ApparentPower => &[Unit::VoltAmpere, Unit::KiloVoltAmpere],
+            Self::Aqi | Self::Date | Self::Enum | Self::Ph | Self::Timestamp => &[],
+            Self::Area => AREA,
+            Self::AtmosphericPressure | Self::Pressure => PRESSURE,
+            Self::Battery | Self::Humidity | Self::Moisture | Self::PowerFactor => PERCENTAGE,
+            Self::BloodGlucoseConcentration => {
+                &[Unit::MilligramPerDeciliter, Unit::MillimolePerLiter]
+            }
+            Self::Co2 => &[Unit::PartsPerMillion],
+            Self::Co => &[
+                Unit::PartsPerMillion,
+                Unit::MilligramPerCubicMeter,
+                Unit::MicrogramPerCubicMeter,
+            ],
+            Self::Conductivity => &[
+                Unit::SiemensPerCentimeter,
+                Unit::MicrosiemensPerCentimeter,
+                Unit::MillisiemensPerCentimeter,
+            ],
+            Self::Current => &[Unit::Milliampere, Unit::Ampere],
+            Self::DataRate => DATA_RATE,
+            Self::DataSize => DATA_SIZE,
+            Self::Distance => DISTANCE,
+            Self::Duration => DURATION,
+            Self::Energy | Self::EnergyStorage => ENERGY,
+            Self::EnergyDistance => &[
+                Unit::KilowattHourPer100Kilometers,
+                Unit::WattHourPerKilometer,
+                Unit::MilesPerKilowattHour,
+                Unit::KilometersPerKilowattHour,
+            ],
+            Self::Frequency => &[
+                Unit::Hertz,
+                Unit::Kilohertz,
+                Unit::Megahertz,
+                Unit::Gigahertz,
+            ],
+            Self::Gas => GAS,
+            Self::Illuminance => &[Unit::Lux],
+            Self::Irradiance => &[Unit::WattPerSquareMeter, Unit::BtuPerHourSquareFoot],
+            Self::Monetary => return None,
+            Self::NitrogenDioxide
+            | Self::NitrogenMonoxide
+            | Self::NitrousOxide
+            | Self::Ozone
+            | Self::Pm1
+            | Self::Pm25
+            | Self::Pm10
+            | Self::SulphurDioxide
+            | Self::VolatileOrganicCompounds => MICROGRAMS,
+            Self::Power => POWER,
+            Self::Precipitation => PRECIPITATION,
+            Self::PrecipitationIntensity => PRECIPITATION_INTENSITY,
+            Self::ReactiveEnergy => &[
+                Unit::VoltAmpereReactiveHour,
+                Unit::KiloVoltAmpereReactiveHour,
+            ],
+            Self::ReactivePower => &[Unit::VoltAmpereReactive, Unit::KiloVoltAmpereReactive],
+            Self::SignalStrength => &[Unit::Decibel, Unit::DecibelMilliwatt],
+            Self::SoundPressure => &[Unit::Decibel, Unit::WeightedDecibelA],
+            Self::Speed => SPEED,
+            Self::Temperature => TEMPERATURE,
+            Self::VolatileOrganicCompoundsParts => &[Unit::PartsPerMillion, Unit::PartsPerBillion],
+            Self::Voltage => &[
+                Unit::Microvolt,
+                Unit::Millivolt,
+                Unit::Volt,
+                Unit::Kilovolt,
+                Unit::Megavolt,
+            ],
+            Self::Volume | Self::VolumeStorage => VOLUME,
+            Self::VolumeFlowRate => VOLUME_FLOW_RATE,
+            Self::Water => WATER,
+            Self::Weight => WEIGHT,
+            Self::WindDirection => &[Unit::Degree],
+            Self::WindSpeed => WIND_SPEED,
+        })
+    }
+
+    /// Check if a sensor of this class may omit the unit.
+    pub fn allows_no_unit(&self) -> bool {
+        matches!(self.units(), None | Some([])) || matches!(self, Self::PowerFactor)
+    }
+
+    /// The state classes a sensor of this class accepts.
+    ///
+    /// An empty list means that no state class must be used.
+    pub fn state_classes(&self) -> &'static [StateClass] {
+        match self {
+            Self::Date | Self::Enum | Self::Timestamp => &[],
+            Self::Energy
+            | Self::Gas
+            | Self::Precipitation
+            | Self::ReactiveEnergy
+            | Self::Volume
+            | Self::Water => TOTAL,
+            Self::Monetary => &[StateClass::Total],
+            Self::DataSize | Self::Distance | Self::Duration | Self::Weight => ANY,
+            Self::WindDirection => &[StateClass::MeasurementAngle],
+            _ => MEASUREMENT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{NumberDiscovery, SensorDiscovery};
+    use std::str::FromStr;
+    use strum::VariantNames;
+
+    #[test]
+    fn test_round_trip() {
+        for name in Unit::VARIANTS {
+            let unit = Unit::from_str(name).unwrap();
+            assert_eq!(unit.as_ref(), *name);
+            assert_eq!(String::from(unit), *name);
+        }
+        assert_eq!(Unit::from_str("°C").unwrap(), Unit::Celsius);
+        assert!(Unit::from_str("celsius").is_err());
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(SensorClass::Temperature.units(), Some(TEMPERATURE));
+        assert!(SensorClass::Power
+            .units()
+            .unwrap()
+            .contains(&Unit::Kilowatt));
+        assert_eq!(SensorClass::Monetary.units(), None);
+        assert_eq!(SensorClass::Timestamp.units(), Some(&[][..]));
+
+        assert!(SensorClass::Monetary.allows_no_unit());
+        assert!(SensorClass::Enum.allows_no_unit());
+        assert!(SensorClass::PowerFactor.allows_no_unit());
+        assert!(!SensorClass::Temperature.allows_no_unit());
+
+        assert_eq!(SensorClass::Temperature.state_classes(), MEASUREMENT);
+        assert_eq!(SensorClass::Energy.state_classes(), TOTAL);
+        assert_eq!(SensorClass::Enum.state_classes(), &[]);
+    }
+
+    #[test]
+    fn test_discovery() {
+        let sensor = SensorDiscovery::new("sensor/state")
+            .unit_of_measurement(Unit::Celsius)
+            .build();
+        assert_eq!(sensor.unit_of_measurement.as_deref(), Some("°C"));
+
+        let number = NumberDiscovery::new("number/set")
+            .unit_of_measurement(Unit::Percentage)
+            .build();
+        assert_eq!(number.unit_of_measurement.as_deref(), Some("%"));
+    }
+}
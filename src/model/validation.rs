@@ -0,0 +1,142 @@
+use crate::model::{Component, DeviceClass, Discovery, SensorClass, StateClass, Unit};
+use std::str::FromStr;
+
+/// A problem with discovery information, which Home Assistant would reject or mis-handle.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+    #[error("device class {device_class} is not valid for component {component}")]
+    InvalidDeviceClass {
+        device_class: DeviceClass,
+        component: Component,
+    },
+    #[error("unit {unit:?} is not valid for device class {}", device_class.as_ref())]
+    InvalidUnit {
+        device_class: SensorClass,
+        unit: String,
+    },
+    #[error("device class {} requires a unit", .0.as_ref())]
+    MissingUnit(SensorClass),
+    #[error("state class {state_class:?} is not valid for device class {}", device_class.as_ref())]
+    InvalidStateClass {
+        device_class: SensorClass,
+        state_class: StateClass,
+    },
+}
+
+impl Discovery {
+    /// Validate the discovery information for an entity of the provided component.
+    ///
+    /// This checks the device class, and for sensors the unit of measurement and the state
+    /// class. All problems found are reported.
+    pub fn validate(&self, component: Component) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+
+        if let Some(device_class) = &self.device_class {
+            if !device_class.is_valid_for(component) {
+                errors.push(ValidationError::InvalidDeviceClass {
+                    device_class: device_class.clone(),
+                    component,
+                });
+            } else if let DeviceClass::Sensor(sensor_class) =
+                device_class.clone().resolve(component)
+            {
+                self.validate_sensor(sensor_class, &mut errors);
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    fn validate_sensor(&self, device_class: SensorClass, errors: &mut Vec<ValidationError>) {
+        match (&self.unit_of_measurement, device_class.units()) {
+            (Some(unit), Some(units))
+                if !Unit::from_str(unit).is_ok_and(|unit| units.contains(&unit)) =>
+            {
+                errors.push(ValidationError::InvalidUnit {
+                    device_class,
+                    unit: unit.clone(),
+                });
+            }
+            (None, _) if !device_class.allows_no_unit() => {
+                errors.push(ValidationError::MissingUnit(device_class));
+            }
+            _ => {}
+        }
+
+        if let Some(state_class) = self.state_class {
+            if !device_class.state_classes().contains(&state_class) {
+                errors.push(ValidationError::InvalidStateClass {
+                    device_class,
+                    state_class,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{BinarySensorClass, SensorDiscovery};
+
+    #[test]
+    fn test_valid() {
+        let discovery = SensorDiscovery::new("sensor/state")
+            .device_class(SensorClass::Temperature)
+            .unit_of_measurement(Unit::Celsius)
+            .state_class(StateClass::Measurement)
+            .build();
+        assert_eq!(discovery.validate(Component::Sensor), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let discovery = SensorDiscovery::new("sensor/state")
+            .device_class(SensorClass::Temperature)
+            .unit_of_measurement("%")
+            .build();
+        assert_eq!(
+            discovery.validate(Component::Sensor),
+            Err(vec![ValidationError::InvalidUnit {
+                device_class: SensorClass::Temperature,
+                unit: "%".into()
+            }])
+        );
+
+        let discovery = SensorDiscovery::new("sensor/state")
+            .device_class(SensorClass::Date)
+            .state_class(StateClass::TotalIncreasing)
+            .build();
+        assert_eq!(
+            discovery.validate(Component::Sensor),
+            Err(vec![ValidationError::InvalidStateClass {
+                device_class: SensorClass::Date,
+                state_class: StateClass::TotalIncreasing,
+            }])
+        );
+
+        let discovery = Discovery {
+            device_class: Some(BinarySensorClass::Motion.into()),
+            ..Default::default()
+        };
+        assert!(discovery.validate(Component::Sensor).is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let discovery: Discovery = serde_json::from_str(
+            r#"{"device_class": "power", "unit_of_measurement": "kW", "state_class": "total"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            discovery.validate(Component::Sensor),
+            Err(vec![ValidationError::InvalidStateClass {
+                device_class: SensorClass::Power,
+                state_class: StateClass::Total,
+            }])
+        );
+    }
+}
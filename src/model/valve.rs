@@ -0,0 +1,162 @@
+use crate::model::{
+    is_configured_payload, is_payload, is_topic, parse_integer, Command, CommandError, Discovery,
+    ValveClass,
+};
+
+// also see: https://www.home-assistant.io/integrations/valve.mqtt/
+
+/// Builder for a valve.
+///
+/// Commands received on the command topic can be decoded into a [`ValveCommand`].
+#[derive(Clone, Debug)]
+pub struct ValveDiscovery {
+    discovery: Discovery,
+}
+
+impl ValveDiscovery {
+    pub fn new(command_topic: impl Into<String>) -> Self {
+        Self {
+            discovery: Discovery {
+                command_topic: Some(command_topic.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn device_class(mut self, device_class: ValveClass) -> Self {
+        self.discovery.device_class = Some(device_class.into());
+        self
+    }
+
+    /// Set the topic the [`ValveState`] or position is published on.
+    pub fn state_topic(mut self, topic: impl Into<String>) -> Self {
+        self.discovery.state_topic = Some(topic.into());
+        self
+    }
+
+    /// Override the default command payloads (`OPEN`, `CLOSE`).
+    pub fn payloads(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.discovery.payload_open = Some(open.into());
+        self.discovery.payload_close = Some(close.into());
+        self
+    }
+
+    /// Enable stopping the valve, using the provided payload.
+    pub fn payload_stop(mut self, stop: impl Into<String>) -> Self {
+        self.discovery.payload_stop = Some(stop.into());
+        self
+    }
+
+    /// Report and accept a position in the range of `closed` to `open`, instead of payloads.
+    pub fn position(mut self, closed: i64, open: i64) -> Self {
+        self.discovery.reports_position = Some(true);
+        self.discovery.position_closed = Some(closed);
+        self.discovery.position_open = Some(open);
+        self
+    }
+
+    pub fn build(self) -> Discovery {
+        self.discovery
+    }
+}
+
+impl From<ValveDiscovery> for Discovery {
+    fn from(value: ValveDiscovery) -> Self {
+        value.build()
+    }
+}
+
+/// The state of a valve.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValveState {
+    Open,
+    Opening,
+    Closed,
+    Closing,
+}
+
+impl ValveState {
+    /// The payload for this state, as configured by the discovery information.
+    pub fn payload<'a>(&self, discovery: &'a Discovery) -> &'a str {
+        let (configured, default) = match self {
+            Self::Open => (&discovery.state_open, "open"),
+            Self::Opening => (&discovery.state_opening, "opening"),
+            Self::Closed => (&discovery.state_closed, "closed"),
+            Self::Closing => (&discovery.state_closing, "closing"),
+        };
+        configured.as_deref().unwrap_or(default)
+    }
+}
+
+/// A command for a valve.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValveCommand {
+    Open,
+    Close,
+    /// Only available if `payload_stop` is configured.
+    Stop,
+    /// Set the position, in the range of `position_closed` to `position_open`.
+    ///
+    /// Only available if the valve reports its position. In this case, Home Assistant will
+    /// also send the open and closed positions instead of open and close commands.
+    SetPosition(i64),
+}
+
+impl Command for ValveCommand {
+    fn decode(
+        discovery: &Discovery,
+        topic: &str,
+        payload: &[u8],
+    ) -> Option<Result<Self, CommandError>> {
+        if !is_topic(&discovery.command_topic, topic) {
+            return None;
+        }
+
+        Some(if is_configured_payload(payload, &discovery.payload_stop) {
+            Ok(Self::Stop)
+        } else if discovery.reports_position == Some(true) {
+            parse_integer(payload).map(Self::SetPosition)
+        } else if is_payload(payload, &discovery.payload_open, "OPEN") {
+            Ok(Self::Open)
+        } else if is_payload(payload, &discovery.payload_close, "CLOSE") {
+            Ok(Self::Close)
+        } else {
+            Err(CommandError::invalid_payload(payload))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let discovery = ValveDiscovery::new("valve/set")
+            .payload_stop("STOP")
+            .build();
+        assert_eq!(
+            ValveCommand::decode(&discovery, "valve/set", b"OPEN")
+                .unwrap()
+                .unwrap(),
+            ValveCommand::Open
+        );
+        assert_eq!(
+            ValveCommand::decode(&discovery, "valve/set", b"STOP")
+                .unwrap()
+                .unwrap(),
+            ValveCommand::Stop
+        );
+
+        let discovery = ValveDiscovery::new("valve/set").position(0, 255).build();
+        assert_eq!(
+            ValveCommand::decode(&discovery, "valve/set", b"128")
+                .unwrap()
+                .unwrap(),
+            ValveCommand::SetPosition(128)
+        );
+        assert!(ValveCommand::decode(&discovery, "valve/set", b"STOP")
+            .unwrap()
+            .is_err());
+    }
+}